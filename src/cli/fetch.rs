@@ -0,0 +1,365 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    thread,
+};
+
+use anyhow::Context;
+use clap::Args;
+use mpd::{Query, Term};
+
+use crate::{
+    config::{overrides::resolve_track_config, paths::get_mapping_path},
+    lyrics::{
+        mapping::LyricsMapping,
+        parser::{find_lyrics_for_track, format_lrc},
+        provider::{Providers, build_named_providers, embedded, lrc, sanitize_file_name},
+        ranking::rank_candidates,
+    },
+    model::{config::Config, track::TrackInfo},
+    player::connection::connect,
+};
+
+#[derive(Debug, Args)]
+pub struct FetchArgs {
+    #[arg(long, help = "只处理指定的 MPD 播放列表，默认处理整个音乐库")]
+    pub playlist: Option<String>,
+    #[arg(short, long, help = "同时处理的歌曲数，默认使用 [fetch] jobs")]
+    pub jobs: Option<usize>,
+    #[arg(long, help = "只列出缺少歌词的歌曲，不请求歌词也不写入文件")]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        help = "相对于 lyrics_dir 的文件名模板，默认使用 [fetch] template"
+    )]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    HasLyrics,
+    Missing(PathBuf),
+    Exists(PathBuf),
+    Fetched(PathBuf),
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Debug, Default)]
+struct Summary {
+    total: usize,
+    has_lyrics: usize,
+    missing: usize,
+    exists: usize,
+    fetched: usize,
+    not_found: usize,
+    failed: usize,
+}
+
+impl Summary {
+    fn record(&mut self, file: &str, outcome: &Outcome) {
+        self.total += 1;
+        match outcome {
+            Outcome::HasLyrics => self.has_lyrics += 1,
+            Outcome::Missing(path) => {
+                self.missing += 1;
+                println!("[缺少] {file} -> {}", path.display());
+            }
+            Outcome::Exists(path) => {
+                self.exists += 1;
+                println!("[已存在] {file} -> {}", path.display());
+            }
+            Outcome::Fetched(path) => {
+                self.fetched += 1;
+                println!("[已获取] {file} -> {}", path.display());
+            }
+            Outcome::NotFound => {
+                self.not_found += 1;
+                println!("[未找到] {file}");
+            }
+            Outcome::Failed(error) => {
+                self.failed += 1;
+                println!("[失败] {file}: {error}");
+            }
+        }
+    }
+
+    fn print(&self, dry_run: bool) {
+        println!();
+        println!("共 {} 首歌曲，已有歌词 {}", self.total, self.has_lyrics);
+        if dry_run {
+            println!("缺少歌词 {}，目标文件已存在 {}", self.missing, self.exists);
+        } else {
+            println!(
+                "新获取 {}，未找到 {}，目标文件已存在 {}，失败 {}",
+                self.fetched, self.not_found, self.exists, self.failed
+            );
+        }
+    }
+}
+
+// 支持 {artist} {title} {album} {stem} {dir}，{dir} 为歌曲在 MPD 中的目录
+fn render_template(template: &str, track: &TrackInfo) -> PathBuf {
+    let path = Path::new(&track.file);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&track.file);
+    let dir = path.parent().and_then(|p| p.to_str()).unwrap_or_default();
+
+    let variables = [
+        (
+            "artist",
+            track.artist.as_deref().unwrap_or("Unknown Artist"),
+        ),
+        ("title", track.title.as_deref().unwrap_or(stem)),
+        ("album", track.album.as_deref().unwrap_or("Unknown Album")),
+        ("stem", stem),
+    ];
+    let rendered = variables
+        .iter()
+        .fold(template.to_string(), |acc, (key, value)| {
+            acc.replace(&format!("{{{key}}}"), &sanitize_file_name(value))
+        })
+        .replace("{dir}", dir);
+
+    rendered
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != "..")
+        .collect()
+}
+
+// 只按文件名和内嵌标签判断是否已有歌词：模糊匹配每首歌都要遍历歌词目录，在线来源每首歌都要发送请求
+fn local_providers(config: &Config) -> Providers {
+    let names: Vec<String> = config
+        .lyrics
+        .providers
+        .iter()
+        .filter(|name| [lrc::NAME, embedded::NAME].contains(&name.as_str()))
+        .cloned()
+        .collect();
+    build_named_providers(&names, config)
+}
+
+struct Fetcher<'a> {
+    config: &'a Config,
+    mapping: LyricsMapping,
+    local: Providers,
+    remote: Providers,
+    template: String,
+    dry_run: bool,
+    // 同一目录的覆盖配置出错时只提示一次
+    warned: Mutex<HashSet<String>>,
+}
+
+impl Fetcher<'_> {
+    fn process(&self, track: &TrackInfo) -> Outcome {
+        let (track_config, warnings) = resolve_track_config(self.config, track);
+        if let Ok(mut warned) = self.warned.lock() {
+            for warning in warnings {
+                if warned.insert(warning.clone()) {
+                    eprintln!("警告: {warning}");
+                }
+            }
+        }
+        let local = if track_config.lyrics == self.config.lyrics {
+            self.local.clone()
+        } else {
+            local_providers(&track_config)
+        };
+        if !find_lyrics_for_track(&track_config, &self.mapping, &local, track).is_empty() {
+            return Outcome::HasLyrics;
+        }
+
+        let target = track_config
+            .paths
+            .lyrics_dir
+            .join(render_template(&self.template, track));
+        if target.exists() {
+            return Outcome::Exists(target);
+        }
+        if self.dry_run {
+            return Outcome::Missing(target);
+        }
+
+        // 单个来源出错时继续尝试其他来源
+        let candidates = self
+            .remote
+            .iter()
+            .filter_map(|provider| provider.fetch(track).ok())
+            .flatten();
+        let Some(best) = rank_candidates(track, candidates).into_iter().next() else {
+            return Outcome::NotFound;
+        };
+
+        let written = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&target, format_lrc(&best.candidate.lyrics)));
+        match written {
+            Ok(()) => Outcome::Fetched(target),
+            Err(e) => Outcome::Failed(format!("写入 {} 失败: {e}", target.display())),
+        }
+    }
+}
+
+fn load_tracks(config: &Config, playlist: Option<&str>) -> anyhow::Result<Vec<TrackInfo>> {
+    let mut client = connect(&config.mpd)?;
+    let songs = match playlist {
+        Some(name) => client
+            .playlist(name)
+            .with_context(|| format!("读取播放列表失败: {name}"))?,
+        // mpd 库没有 listallinfo，用匹配所有歌曲的 find 一次取回路径和标签
+        None => client
+            .find(Query::new().and(Term::LastMod, "0"), None)
+            .context("读取MPD音乐库失败")?,
+    };
+    Ok(songs.iter().map(TrackInfo::from).collect())
+}
+
+pub fn run(config: Config, args: FetchArgs) -> anyhow::Result<()> {
+    let tracks = load_tracks(&config, args.playlist.as_deref())?;
+    let jobs = args.jobs.unwrap_or(config.fetch.jobs).max(1);
+    let fetcher = Fetcher {
+        config: &config,
+        mapping: LyricsMapping::load(&get_mapping_path()).unwrap_or_default(),
+        local: local_providers(&config),
+        remote: build_named_providers(&config.fetch.providers, &config),
+        template: args
+            .template
+            .unwrap_or_else(|| config.fetch.template.clone()),
+        dry_run: args.dry_run,
+        warned: Mutex::default(),
+    };
+
+    let queue = Mutex::new(tracks.into_iter());
+    let (sender, receiver) = mpsc::channel();
+    let mut summary = Summary::default();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (queue, fetcher) = (&queue, &fetcher);
+            scope.spawn(move || {
+                loop {
+                    let Some(track) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let outcome = fetcher.process(&track);
+                    if sender.send((track.file, outcome)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (file, outcome) in receiver {
+            summary.record(&file, &outcome);
+        }
+    });
+
+    summary.print(args.dry_run);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::lyrics::{
+        parser::parse_lrc,
+        provider::{LyricsCandidate, LyricsProvider},
+    };
+
+    struct StaticProvider(&'static str);
+
+    impl LyricsProvider for StaticProvider {
+        fn name(&self) -> &'static str {
+            "static"
+        }
+
+        fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+            if track.title.as_deref() != Some("Found") {
+                return Ok(Vec::new());
+            }
+            Ok(vec![LyricsCandidate {
+                source: "static",
+                label: "static".into(),
+                binding: None,
+                lyrics: parse_lrc(self.0),
+            }])
+        }
+    }
+
+    fn track(file: &str, title: &str) -> TrackInfo {
+        TrackInfo {
+            file: file.into(),
+            artist: Some("AC/DC".into()),
+            title: Some(title.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let track = track("Rock/Album/01 Song.flac", "Found");
+        assert_eq!(
+            render_template("{dir}/{stem}.lrc", &track),
+            PathBuf::from("Rock/Album/01 Song.lrc")
+        );
+        assert_eq!(
+            render_template("{artist}/{title}.lrc", &track),
+            PathBuf::from("AC_DC/Found.lrc")
+        );
+        assert_eq!(
+            render_template("../{stem}.lrc", &track),
+            PathBuf::from("01 Song.lrc")
+        );
+    }
+
+    #[test]
+    fn test_process_tracks() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.paths.music_dir = dir.path().join("music");
+        config.paths.lyrics_dir = dir.path().join("lyrics");
+        config.lyrics.providers = vec!["lrc".into(), "lrc-fuzzy".into()];
+
+        let mut fetcher = Fetcher {
+            config: &config,
+            mapping: LyricsMapping::default(),
+            local: local_providers(&config),
+            remote: Arc::new(vec![Box::new(StaticProvider("[00:01.00]line"))]),
+            template: "{dir}/{stem}.lrc".into(),
+            dry_run: true,
+            warned: Mutex::default(),
+        };
+
+        let found = track("Album/found.flac", "Found");
+        let target = config.paths.lyrics_dir.join("Album/found.lrc");
+        assert_eq!(fetcher.process(&found), Outcome::Missing(target.clone()));
+        assert!(!target.exists());
+
+        fetcher.dry_run = false;
+        assert_eq!(fetcher.process(&found), Outcome::Fetched(target.clone()));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "[00:01.00]line\n");
+
+        // 写入后本地来源即可找到歌词
+        assert_eq!(fetcher.process(&found), Outcome::HasLyrics);
+
+        // 只能按标签模糊匹配到的歌词不算已有歌词
+        let missing = track("Album/missing.flac", "Missing");
+        let fuzzy = config.paths.lyrics_dir.join("Other/tagged.lrc");
+        fs::create_dir_all(fuzzy.parent().unwrap()).unwrap();
+        fs::write(&fuzzy, "[ar:AC/DC]\n[ti:Missing]\n[00:01.00]line\n").unwrap();
+        let fuzzy_provider = build_named_providers(&["lrc-fuzzy".into()], &config);
+        assert!(!fuzzy_provider[0].fetch(&missing).unwrap().is_empty());
+        assert_eq!(fetcher.process(&missing), Outcome::NotFound);
+    }
+}
@@ -0,0 +1,40 @@
+use clap::Args;
+
+use crate::{
+    config::paths::get_missing_log_path,
+    lyrics::missing::{load_records, summarize},
+};
+
+#[derive(Debug, Args)]
+pub struct MissingArgs {
+    #[arg(short = 'n', long, help = "最多显示的歌曲数")]
+    pub limit: Option<usize>,
+}
+
+pub fn run(args: MissingArgs) -> anyhow::Result<()> {
+    let path = get_missing_log_path();
+    let entries = summarize(load_records(&path)?);
+    if entries.is_empty() {
+        println!("没有缺少歌词的播放记录 ({})", path.display());
+        return Ok(());
+    }
+
+    println!("  次数  歌曲");
+    for entry in entries.iter().take(args.limit.unwrap_or(usize::MAX)) {
+        let record = &entry.record;
+        let name = match (&record.artist, &record.title) {
+            (Some(artist), Some(title)) => format!("{artist} - {title}"),
+            (None, Some(title)) => title.clone(),
+            _ => record.file.clone(),
+        };
+        println!("{:>6}  {name}", entry.plays);
+        println!(
+            "        {}  [已尝试: {}]",
+            record.file,
+            record.providers.join(", ")
+        );
+    }
+    println!();
+    println!("共 {} 首歌曲缺少歌词", entries.len());
+    Ok(())
+}
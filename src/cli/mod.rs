@@ -0,0 +1,29 @@
+pub mod fetch;
+pub mod missing;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "lyra-rs",
+    version,
+    about = "MPD 终端歌词显示工具，不带子命令时启动歌词界面"
+)]
+pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "使用 [mpd.profiles.<name>] 中定义的 MPD 服务器"
+    )]
+    pub profile: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(about = "为缺少歌词的歌曲批量获取歌词")]
+    Fetch(fetch::FetchArgs),
+    #[command(about = "列出播放过但没有找到歌词的歌曲，按播放次数排序")]
+    Missing(missing::MissingArgs),
+}
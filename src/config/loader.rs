@@ -1,9 +1,20 @@
-use std::fs;
+use std::{env::var, fs, path::Path};
 
 use crate::config::paths;
+use crate::lyrics::provider::unknown_providers;
 use crate::model::config::Config;
 
+// 连接设置的优先级：环境变量 > 配置文件 > 默认值
 pub fn load_config() -> Config {
+    let mut config = read_config_file();
+    config.mpd.apply_env(
+        var("MPD_HOST").ok().as_deref(),
+        var("MPD_PORT").ok().as_deref(),
+    );
+    config
+}
+
+fn read_config_file() -> Config {
     let path = paths::get_config_path();
 
     if !path.exists() {
@@ -19,8 +30,20 @@ pub fn load_config() -> Config {
         }
     };
 
-    match toml::from_str(&content) {
-        Ok(cfg) => cfg,
+    match toml::from_str::<Config>(&content) {
+        Ok(cfg) => {
+            let names = [&cfg.lyrics.providers, &cfg.fetch.providers];
+            for name in names.into_iter().flat_map(|n| unknown_providers(n, &cfg)) {
+                eprintln!("警告: 未知的歌词来源 {name}，已忽略");
+            }
+            if cfg.has_plaintext_password() && readable_by_others(&path) {
+                eprintln!(
+                    "警告: 配置文件 {} 包含密码，但同组或其他用户可以读取，建议执行 chmod 600，或改用 password_file/password_command",
+                    path.display()
+                );
+            }
+            cfg
+        }
         Err(e) => {
             eprintln!("解析配置失败: {e}");
             Config::default()
@@ -28,6 +51,17 @@ pub fn load_config() -> Config {
     }
 }
 
+#[cfg(unix)]
+fn readable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o044 != 0)
+}
+
+#[cfg(not(unix))]
+fn readable_by_others(_path: &Path) -> bool {
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config::paths::get_config_path;
@@ -151,4 +185,19 @@ mod tests {
             assert_eq!(path, expected_path);
         });
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_readable_by_others() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "").unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(!readable_by_others(&path));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        assert!(readable_by_others(&path));
+    }
 }
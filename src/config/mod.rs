@@ -1,2 +1,3 @@
 pub mod loader;
-mod paths;
+pub mod overrides;
+pub mod paths;
@@ -0,0 +1,200 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::model::{config::Config, track::TrackInfo};
+
+pub const OVERRIDE_FILE_NAME: &str = ".lyra.toml";
+
+// 连接、路径和输出延迟设置只能在全局配置中修改
+const GLOBAL_ONLY_KEYS: [&str; 3] = ["mpd", "paths", "output_latency"];
+
+// 将覆盖表展开为逐个字段，全局配置中同样是表的键继续向下展开
+fn overlay_fields(base: &Table, overlay: Table, prefix: &[String]) -> Vec<(Vec<String>, Value)> {
+    let mut fields = Vec::new();
+    for (key, value) in overlay {
+        let mut path = prefix.to_vec();
+        path.push(key.clone());
+        match (base.get(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => {
+                fields.extend(overlay_fields(base, overlay, &path));
+            }
+            (_, value) => fields.push((path, value)),
+        }
+    }
+    fields
+}
+
+fn set_field(table: &mut Table, path: &[String], value: Value) {
+    let Some((key, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = table;
+    for parent in parents {
+        match current.get_mut(parent) {
+            Some(Value::Table(next)) => current = next,
+            _ => return,
+        }
+    }
+    current.insert(key.clone(), value);
+}
+
+// 从音乐目录开始逐级向下，直到歌曲所在目录
+fn override_dirs(music_dir: &Path, track_file: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![music_dir.to_path_buf()];
+    if track_file.contains("://") {
+        return dirs;
+    }
+
+    let mut current = music_dir.to_path_buf();
+    if let Some(parent) = Path::new(track_file).parent() {
+        for component in parent.components() {
+            current.push(component);
+            dirs.push(current.clone());
+        }
+    }
+    dirs
+}
+
+fn read_override(path: &Path) -> Result<Table, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut table: Table = toml::from_str(&content).map_err(|e| e.message().to_string())?;
+    for key in GLOBAL_ONLY_KEYS {
+        table.remove(key);
+    }
+    Ok(table)
+}
+
+// 将目录链上的 .lyra.toml 依次合并到全局配置上，越靠近歌曲的目录优先级越高；
+// 无法读取的文件和取值无效的字段被忽略并返回警告，不影响播放和同一文件中的其他设置
+pub fn resolve_track_config(global: &Config, track: &TrackInfo) -> (Config, Vec<String>) {
+    let mut warnings = Vec::new();
+    let overlays: Vec<(PathBuf, Table)> = override_dirs(&global.paths.music_dir, &track.file)
+        .into_iter()
+        .map(|dir| dir.join(OVERRIDE_FILE_NAME))
+        .filter(|path| path.exists())
+        .filter_map(|path| match read_override(&path) {
+            Ok(table) => Some((path, table)),
+            Err(e) => {
+                warnings.push(format!("忽略 {}: {e}", path.display()));
+                None
+            }
+        })
+        .collect();
+
+    let mut config = global.clone();
+    let Ok(Value::Table(mut table)) = Value::try_from(global) else {
+        return (config, warnings);
+    };
+    for (path, overlay) in overlays {
+        for (keys, value) in overlay_fields(&table, overlay, &[]) {
+            let mut merged = table.clone();
+            set_field(&mut merged, &keys, value);
+            match Value::Table(merged.clone()).try_into() {
+                Ok(resolved) => {
+                    table = merged;
+                    config = resolved;
+                }
+                Err(e) => warnings.push(format!(
+                    "忽略 {} 中的 {}: {}",
+                    path.display(),
+                    keys.join("."),
+                    e.message()
+                )),
+            }
+        }
+    }
+    (config, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::config::OffsetStore;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_overlay_fields() {
+        let mut base: Table = toml::from_str("a = 1\n[t]\nx = 1\ny = 2").unwrap();
+        let overlay: Table = toml::from_str("b = 2\n[t]\ny = 3").unwrap();
+        for (path, value) in overlay_fields(&base, overlay, &[]) {
+            set_field(&mut base, &path, value);
+        }
+
+        let expected: Table = toml::from_str("a = 1\nb = 2\n[t]\nx = 1\ny = 3").unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_resolve_directory_chain() {
+        let music = tempdir().unwrap();
+        let album = music.path().join("Artist/Album");
+        fs::create_dir_all(&album).unwrap();
+        fs::write(
+            music.path().join(OVERRIDE_FILE_NAME),
+            "[sync]\noffset_ms = 100\n[lyric_style]\ncolor = \"#FFFFFF\"",
+        )
+        .unwrap();
+        fs::write(
+            album.join(OVERRIDE_FILE_NAME),
+            "[sync]\noffset_ms = -250\n[lyrics]\nproviders = [\"lrclib\"]\nencoding = \"gbk\"\n[mpd]\nport = 1",
+        )
+        .unwrap();
+        // 格式错误的文件被忽略
+        fs::write(
+            music.path().join("Artist").join(OVERRIDE_FILE_NAME),
+            "[sync",
+        )
+        .unwrap();
+
+        let mut global = Config::default();
+        global.paths.music_dir = music.path().to_path_buf();
+
+        let track = TrackInfo {
+            file: "Artist/Album/01.flac".into(),
+            ..Default::default()
+        };
+        let (config, warnings) = resolve_track_config(&global, &track);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Artist"));
+        assert_eq!(config.sync.offset_ms, -250);
+        assert_eq!(config.lyrics.providers, ["lrclib"]);
+        assert_eq!(config.lyrics.encoding, "gbk");
+        assert_eq!(config.lyrics.match_threshold, 0.8);
+        assert_eq!(config.lyric_style.color, "#FFFFFF");
+        assert_eq!(config.mpd, global.mpd);
+
+        let other = TrackInfo {
+            file: "Other/01.flac".into(),
+            ..Default::default()
+        };
+        let (config, warnings) = resolve_track_config(&global, &other);
+        assert!(warnings.is_empty());
+        assert_eq!(config.sync.offset_ms, 100);
+        assert_eq!(config.lyrics, global.lyrics);
+    }
+
+    #[test]
+    fn test_resolve_invalid_field() {
+        let music = tempdir().unwrap();
+        fs::write(
+            music.path().join(OVERRIDE_FILE_NAME),
+            "[sync]\noffset_ms = \"fast\"\nsave_to = \"sticker\"\n[lyrics]\nencoding = \"gbk\"",
+        )
+        .unwrap();
+
+        let mut global = Config::default();
+        global.paths.music_dir = music.path().to_path_buf();
+
+        // 错误的字段只忽略自身，同一文件中的其他设置仍然生效
+        let (config, warnings) = resolve_track_config(&global, &TrackInfo::default());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("sync.offset_ms"));
+        assert_eq!(config.sync.offset_ms, global.sync.offset_ms);
+        assert_eq!(config.sync.save_to, OffsetStore::Sticker);
+        assert_eq!(config.lyrics.encoding, "gbk");
+    }
+}
@@ -5,3 +5,26 @@ pub fn get_config_path() -> PathBuf {
         .expect("无法获取用户主目录")
         .join(".config/lyra/config.toml")
 }
+
+pub fn get_data_dir() -> PathBuf {
+    dirs::data_dir().expect("无法获取用户数据目录").join("lyra")
+}
+
+pub fn get_mapping_path() -> PathBuf {
+    get_data_dir().join("mappings.toml")
+}
+
+pub fn get_offsets_path() -> PathBuf {
+    get_data_dir().join("offsets.toml")
+}
+
+// 部分平台没有单独的状态目录，此时使用数据目录
+pub fn get_state_dir() -> PathBuf {
+    dirs::state_dir()
+        .map(|dir| dir.join("lyra"))
+        .unwrap_or_else(get_data_dir)
+}
+
+pub fn get_missing_log_path() -> PathBuf {
+    get_state_dir().join("missing.jsonl")
+}
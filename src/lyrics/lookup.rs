@@ -0,0 +1,43 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{
+    config::paths::get_mapping_path,
+    lyrics::{
+        mapping::LyricsMapping, parser::find_lyrics_for_track, provider::Providers,
+        ranking::ScoredCandidate,
+    },
+    model::{config::Config, track::TrackInfo},
+};
+
+#[derive(Debug)]
+pub struct LookupResult {
+    pub track: TrackInfo,
+    pub candidates: Vec<ScoredCandidate>,
+    // 没有找到歌词时所有来源都被查询过，记录到缺少歌词的列表中
+    pub providers: Vec<String>,
+}
+
+// 在后台线程中查找歌词，网络请求或外部命令不会阻塞界面
+pub fn spawn_lookup(
+    config: Config,
+    providers: Providers,
+    track: TrackInfo,
+) -> Receiver<LookupResult> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        // 每次查找都重新读取，选择歌词后的绑定立即生效
+        let mapping = LyricsMapping::load(&get_mapping_path()).unwrap_or_default();
+        let candidates = find_lyrics_for_track(&config, &mapping, &providers, &track);
+        let _ = sender.send(LookupResult {
+            track,
+            candidates,
+            providers: providers.iter().map(|p| p.name().to_string()).collect(),
+        });
+    });
+
+    receiver
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+// 手动指定的歌词来源
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum LyricsBinding {
+    File { path: PathBuf },
+    Embedded,
+}
+
+// 以 MPD 路径为键的歌曲与歌词的对应关系
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LyricsMapping {
+    tracks: BTreeMap<String, LyricsBinding>,
+}
+
+impl LyricsMapping {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, track_file: &str) -> Option<&LyricsBinding> {
+        self.tracks.get(track_file)
+    }
+
+    pub fn bind(&mut self, track_file: &str, binding: LyricsBinding) {
+        self.tracks.insert(track_file.to_string(), binding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_missing_mapping() {
+        let dir = tempdir().unwrap();
+        let mapping = LyricsMapping::load(&dir.path().join("mappings.toml")).unwrap();
+        assert_eq!(mapping, LyricsMapping::default());
+    }
+
+    #[test]
+    fn test_save_and_load_mapping() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lyra/mappings.toml");
+
+        let mut mapping = LyricsMapping::default();
+        mapping.bind(
+            "Artist/Album/01 Song.flac",
+            LyricsBinding::File {
+                path: PathBuf::from("/lyrics/Song.lrc"),
+            },
+        );
+        mapping.bind("Artist/Album/02 Other.flac", LyricsBinding::Embedded);
+        mapping.save(&path).unwrap();
+
+        let loaded = LyricsMapping::load(&path).unwrap();
+        assert_eq!(loaded, mapping);
+        assert_eq!(
+            loaded.get("Artist/Album/02 Other.flac"),
+            Some(&LyricsBinding::Embedded)
+        );
+        assert!(loaded.get("missing.flac").is_none());
+    }
+}
@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+
+use crate::model::track::TrackInfo;
+
+// 括号内出现这些词时视为版本修饰，比较前整体去掉
+const NOISE_WORDS: &[&str] = &[
+    "live",
+    "remaster",
+    "remastered",
+    "feat",
+    "ft",
+    "featuring",
+    "version",
+    "edit",
+    "mono",
+    "stereo",
+    "demo",
+    "现场",
+    "伴奏",
+];
+
+const FEAT_MARKERS: &[&str] = &[" feat. ", " feat ", " ft. ", " ft ", " featuring "];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchCandidate {
+    pub path: PathBuf,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+}
+
+impl MatchCandidate {
+    // 优先使用 LRC 头部的 [ar:]/[ti:]，缺失时从 "歌手 - 标题" 形式的文件名推断
+    pub fn new(path: PathBuf, artist: Option<String>, title: Option<String>) -> Self {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let (stem_artist, stem_title) = split_artist_title(&stem);
+
+        Self {
+            artist: artist.or(stem_artist),
+            title: title.or(Some(stem_title)),
+            path,
+        }
+    }
+}
+
+pub fn split_artist_title(s: &str) -> (Option<String>, String) {
+    match s.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => {
+            (Some(artist.trim().to_string()), title.trim().to_string())
+        }
+        _ => (None, s.trim().to_string()),
+    }
+}
+
+pub fn normalize(s: &str) -> String {
+    let mut s = strip_noise_brackets(&s.to_lowercase());
+
+    if let Some((head, tail)) = s.split_once(" - ")
+        && is_noise(tail)
+    {
+        s = head.to_string();
+    }
+
+    for marker in FEAT_MARKERS {
+        if let Some(pos) = s.find(marker) {
+            s.truncate(pos);
+        }
+    }
+
+    s.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_noise(s: &str) -> bool {
+    s.split(|c: char| !c.is_alphanumeric())
+        .any(|word| NOISE_WORDS.contains(&word))
+        || NOISE_WORDS
+            .iter()
+            .filter(|w| !w.is_ascii())
+            .any(|w| s.contains(w))
+}
+
+fn strip_noise_brackets(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let close = match c {
+            '(' => ')',
+            '[' => ']',
+            '（' => '）',
+            '【' => '】',
+            _ => {
+                result.push(c);
+                continue;
+            }
+        };
+
+        match s[start + c.len_utf8()..].find(close) {
+            Some(len) if is_noise(&s[start + c.len_utf8()..start + c.len_utf8() + len]) => {
+                let end = start + c.len_utf8() + len + close.len_utf8();
+                while chars.peek().is_some_and(|(i, _)| *i < end) {
+                    chars.next();
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 0.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    1.0 - prev[b.len()] as f64 / max_len as f64
+}
+
+pub fn score(track: &TrackInfo, candidate: &MatchCandidate) -> f64 {
+    let (track_artist, track_title) = match &track.title {
+        Some(title) => (track.artist.clone(), title.clone()),
+        None => {
+            let stem = Path::new(&track.file)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&track.file);
+            let (artist, title) = split_artist_title(stem);
+            (track.artist.clone().or(artist), title)
+        }
+    };
+
+    let Some(candidate_title) = &candidate.title else {
+        return 0.0;
+    };
+
+    let title_score = similarity(&normalize(&track_title), &normalize(candidate_title));
+
+    let ordered = match (&track_artist, &candidate.artist) {
+        (Some(a), Some(b)) => 0.7 * title_score + 0.3 * similarity(&normalize(a), &normalize(b)),
+        _ => title_score,
+    };
+
+    // 文件名也可能是 "标题 - 歌手" 的顺序
+    let swapped = match (&track_artist, &candidate.artist) {
+        (Some(a), Some(b)) => {
+            0.7 * similarity(&normalize(&track_title), &normalize(b))
+                + 0.3 * similarity(&normalize(a), &normalize(candidate_title))
+        }
+        _ => 0.0,
+    };
+
+    ordered.max(swapped)
+}
+
+pub fn find_best_match<T>(
+    track: &TrackInfo,
+    candidates: impl IntoIterator<Item = (MatchCandidate, T)>,
+    threshold: f64,
+) -> Option<T> {
+    candidates
+        .into_iter()
+        .map(|(c, item)| (score(track, &c), item))
+        .filter(|(s, _)| *s >= threshold)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, item)| item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(artist: &str, title: &str) -> TrackInfo {
+        TrackInfo {
+            file: format!("{artist}/{title}.flac"),
+            artist: Some(artist.into()),
+            title: Some(title.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_strips_version_suffixes() {
+        assert_eq!(normalize("Yesterday (Live)"), "yesterday");
+        assert_eq!(normalize("Yesterday - Remastered 2009"), "yesterday");
+        assert_eq!(normalize("Hello feat. Someone"), "hello");
+        assert_eq!(normalize("Song [Radio Edit]"), "song");
+        assert_eq!(normalize("Song (Reprise)"), "song reprise");
+        assert_eq!(normalize("大雨还在下（现场）"), "大雨还在下");
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert_eq!(similarity("", ""), 0.0);
+        assert!(similarity("kitten", "sitting") < 0.6);
+    }
+
+    #[test]
+    fn test_candidate_from_file_stem() {
+        let c = MatchCandidate::new(PathBuf::from("/lrc/1个球 - 大雨还在下.lrc"), None, None);
+        assert_eq!(c.artist.as_deref(), Some("1个球"));
+        assert_eq!(c.title.as_deref(), Some("大雨还在下"));
+    }
+
+    #[test]
+    fn test_find_best_match() {
+        let candidates = [
+            MatchCandidate::new(
+                PathBuf::from("a.lrc"),
+                Some("Other".into()),
+                Some("Song".into()),
+            ),
+            MatchCandidate::new(PathBuf::from("Artist - Song (Live).lrc"), None, None),
+            MatchCandidate::new(PathBuf::from("c.lrc"), None, Some("Different".into())),
+        ];
+
+        let with_paths = || candidates.iter().map(|c| (c.clone(), c.path.clone()));
+
+        let best = find_best_match(&track("Artist", "Song"), with_paths(), 0.8);
+        assert_eq!(best, Some(PathBuf::from("Artist - Song (Live).lrc")));
+
+        assert!(find_best_match(&track("Nobody", "Nothing"), with_paths(), 0.8).is_none());
+    }
+}
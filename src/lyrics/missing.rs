@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::track::TrackInfo;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MissingRecord {
+    // Unix 时间戳(秒)
+    pub played_at: u64,
+    pub file: String,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub providers: Vec<String>,
+}
+
+impl MissingRecord {
+    pub fn new(track: &TrackInfo, providers: Vec<String>) -> Self {
+        Self {
+            played_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            file: track.file.clone(),
+            artist: track.artist.clone(),
+            title: track.title.clone(),
+            providers,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingEntry {
+    pub record: MissingRecord,
+    pub plays: usize,
+}
+
+pub fn append_record(path: &Path, record: &MissingRecord) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+// 跳过写入中断等原因造成的损坏行
+pub fn load_records(path: &Path) -> anyhow::Result<Vec<MissingRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+// 按 MPD 路径去重，保留最近一次记录，按播放次数从多到少排序
+pub fn summarize(records: Vec<MissingRecord>) -> Vec<MissingEntry> {
+    let mut entries: HashMap<String, MissingEntry> = HashMap::new();
+    for record in records {
+        match entries.get_mut(&record.file) {
+            Some(entry) => {
+                entry.plays += 1;
+                if record.played_at >= entry.record.played_at {
+                    entry.record = record;
+                }
+            }
+            None => {
+                entries.insert(record.file.clone(), MissingEntry { record, plays: 1 });
+            }
+        }
+    }
+
+    let mut entries: Vec<MissingEntry> = entries.into_values().collect();
+    entries.sort_by(|a, b| {
+        b.plays
+            .cmp(&a.plays)
+            .then(b.record.played_at.cmp(&a.record.played_at))
+            .then(a.record.file.cmp(&b.record.file))
+    });
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(file: &str, played_at: u64) -> MissingRecord {
+        MissingRecord {
+            played_at,
+            file: file.into(),
+            artist: Some("Artist".into()),
+            title: Some(file.into()),
+            providers: vec!["lrc".into(), "embedded".into()],
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lyra/missing.jsonl");
+
+        append_record(&path, &record("a.flac", 1)).unwrap();
+        fs::write(
+            &path,
+            format!("{}{{\"broken\n", fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+        append_record(&path, &record("b.flac", 2)).unwrap();
+
+        let records = load_records(&path).unwrap();
+        assert_eq!(records, [record("a.flac", 1), record("b.flac", 2)]);
+        assert!(
+            load_records(&dir.path().join("none.jsonl"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut latest = record("a.flac", 5);
+        latest.providers = vec!["lrclib".into()];
+        let entries = summarize(vec![
+            record("a.flac", 1),
+            record("b.flac", 2),
+            latest.clone(),
+            record("c.flac", 3),
+        ]);
+
+        let files: Vec<&str> = entries.iter().map(|e| e.record.file.as_str()).collect();
+        assert_eq!(files, ["a.flac", "c.flac", "b.flac"]);
+        assert_eq!(entries[0].plays, 2);
+        assert_eq!(entries[0].record, latest);
+    }
+}
@@ -1 +1,9 @@
+pub mod lookup;
+pub mod mapping;
+pub mod matcher;
+pub mod missing;
+pub mod offset;
 pub mod parser;
+pub mod provider;
+pub mod ranking;
+pub mod translation;
@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::paths::get_offsets_path, lyrics::parser::set_lrc_offset, model::config::OffsetStore,
+    player::connection::MpdClient,
+};
+
+const STICKER_NAME: &str = "lyra_offset";
+
+// 以 MPD 路径为键的单曲歌词偏移(毫秒)
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TrackOffsets {
+    tracks: BTreeMap<String, i64>,
+}
+
+impl TrackOffsets {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, track_file: &str) -> Option<i64> {
+        self.tracks.get(track_file).copied()
+    }
+
+    // 偏移为 0 时也保留记录，否则会重新使用歌词中的 [offset:] 标签
+    pub fn set(&mut self, track_file: &str, offset_ms: i64) {
+        self.tracks.insert(track_file.to_string(), offset_ms);
+    }
+}
+
+// 读取保存的单曲偏移，LRC 的 [offset:] 标签在解析歌词时已经读取
+pub fn load_offset(
+    store: OffsetStore,
+    track_file: &str,
+    client: Option<&mut MpdClient>,
+) -> Option<i64> {
+    match store {
+        OffsetStore::Lrc => None,
+        // 没有该 sticker 或 MPD 未启用 sticker 数据库时都会返回错误
+        OffsetStore::Sticker => client?
+            .sticker("song", track_file, STICKER_NAME)
+            .ok()?
+            .parse()
+            .ok(),
+        OffsetStore::Database => TrackOffsets::load(&get_offsets_path())
+            .ok()?
+            .get(track_file),
+    }
+}
+
+// 先写入同目录下的临时文件再改名，写入中途出错时不会留下不完整的歌词文件
+fn replace_file(path: &Path, content: &str) -> anyhow::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(content.as_bytes())?;
+    file.as_file()
+        .set_permissions(fs::metadata(path)?.permissions())?;
+    file.persist(path)?;
+    Ok(())
+}
+
+pub fn save_offset(
+    store: OffsetStore,
+    track_file: &str,
+    lyrics_file: Option<&Path>,
+    client: Option<&mut MpdClient>,
+    offset_ms: i64,
+) -> anyhow::Result<()> {
+    match store {
+        OffsetStore::Lrc => {
+            let path = lyrics_file.ok_or_else(|| anyhow!("当前歌词不是 LRC 文件"))?;
+            let content = String::from_utf8(fs::read(path)?)
+                .map_err(|_| anyhow!("只能写入 UTF-8 编码的 LRC 文件"))?;
+            replace_file(path, &set_lrc_offset(&content, offset_ms))
+                .with_context(|| format!("写入 {} 失败", path.display()))?;
+        }
+        OffsetStore::Sticker => {
+            let client = client.ok_or_else(|| anyhow!("MPD 未连接"))?;
+            client.set_sticker("song", track_file, STICKER_NAME, &offset_ms.to_string())?;
+        }
+        OffsetStore::Database => {
+            let path = get_offsets_path();
+            let mut offsets = TrackOffsets::load(&path)?;
+            offsets.set(track_file, offset_ms);
+            offsets.save(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::parser::from_lrc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_and_load_offsets() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lyra/offsets.toml");
+        assert_eq!(TrackOffsets::load(&path).unwrap(), TrackOffsets::default());
+
+        let mut offsets = TrackOffsets::default();
+        offsets.set("Artist/01 Song.flac", -300);
+        offsets.set("Artist/02 Other.flac", 200);
+        offsets.set("Artist/02 Other.flac", 0);
+        offsets.save(&path).unwrap();
+
+        let loaded = TrackOffsets::load(&path).unwrap();
+        assert_eq!(loaded, offsets);
+        assert_eq!(loaded.get("Artist/01 Song.flac"), Some(-300));
+        assert_eq!(loaded.get("Artist/02 Other.flac"), Some(0));
+        assert_eq!(loaded.get("Artist/03 Missing.flac"), None);
+    }
+
+    #[test]
+    fn test_saved_zero_overrides_tag() {
+        let dir = tempdir().unwrap();
+        let lrc = dir.path().join("song.lrc");
+        fs::write(&lrc, "[offset:300]\n[00:01.00]第一句\n").unwrap();
+        let tag = from_lrc(&lrc, None).unwrap().metadata.offset_ms;
+        assert_eq!(tag, Some(300));
+
+        // 把带标签歌词的偏移调回 0 后，保存的 0 优先于标签
+        let path = dir.path().join("offsets.toml");
+        let mut offsets = TrackOffsets::default();
+        offsets.set("song.flac", 0);
+        offsets.save(&path).unwrap();
+        let stored = TrackOffsets::load(&path).unwrap().get("song.flac");
+        assert_eq!(stored.or(tag), Some(0));
+    }
+
+    #[test]
+    fn test_save_offset_to_lrc() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("song.lrc");
+        fs::write(&path, "[ti:标题]\r\n[00:01.00]第一句\r\n").unwrap();
+
+        save_offset(OffsetStore::Lrc, "song.flac", Some(&path), None, 250).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[ti:标题]\r\n[offset:250]\r\n[00:01.00]第一句\r\n"
+        );
+        // 临时文件已改名为歌词文件
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
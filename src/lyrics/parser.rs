@@ -5,12 +5,25 @@ use std::{
 };
 
 use anyhow::Ok;
+use encoding_rs::Encoding;
 use lofty::{file::TaggedFileExt, read_from_path};
 use walkdir::WalkDir;
 
-use crate::model::{
-    config::PathConfig,
-    lyrics::{LyricLine, Lyrics},
+use crate::{
+    config::paths::get_mapping_path,
+    lyrics::{
+        mapping::{LyricsBinding, LyricsMapping},
+        provider::{
+            LyricsCandidate, LyricsProvider, embedded::EmbeddedProvider, lrc::LrcFileProvider,
+        },
+        ranking::{ScoredCandidate, rank_candidates, score_lyrics},
+        translation::attach_translations,
+    },
+    model::{
+        config::Config,
+        lyrics::{LyricLine, Lyrics, LyricsMetadata},
+        track::TrackInfo,
+    },
 };
 
 fn parse_lrc_timestamp(s: &str) -> Option<Duration> {
@@ -19,62 +32,248 @@ fn parse_lrc_timestamp(s: &str) -> Option<Duration> {
     let minutes = parts.next()?.parse::<u64>().ok()?;
     let seconds = parts.next()?.parse::<f64>().ok()?;
 
-    Some(Duration::from_secs(minutes * 60) + Duration::from_millis((seconds * 1000.0) as u64))
+    Some(
+        Duration::from_secs(minutes * 60)
+            + Duration::from_millis((seconds * 1000.0).round() as u64),
+    )
 }
 
-fn from_audio<P: AsRef<Path>>(path: P) -> anyhow::Result<Option<Lyrics>> {
-    let tagged_file = read_from_path(path)?;
+fn parse_lrc_header(line: &str, metadata: &mut LyricsMetadata) -> bool {
+    let Some(inner) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) else {
+        return false;
+    };
+    let Some((key, value)) = inner.split_once(':') else {
+        return false;
+    };
 
-    if let Some(tag) = tagged_file.primary_tag() {
-        if let Some(lyrics) = tag.get_string(&lofty::tag::ItemKey::Lyrics) {
-            let mut lines = Vec::new();
-
-            for line in lyrics.lines() {
-                let mut parts = line.splitn(2, ']');
-                if let (Some(time_part), Some(text)) = (parts.next(), parts.next()) {
-                    if let Some(timestamp) = parse_lrc_timestamp(time_part) {
-                        lines.push(LyricLine {
-                            timestamp,
-                            text: text.trim().to_string(),
-                        });
-                    }
-                }
-            }
-            if !lines.is_empty() {
-                return Ok(Some(Lyrics { lines }));
-            }
+    let value = value.trim();
+    let field = match key.trim().to_ascii_lowercase().as_str() {
+        "ar" => &mut metadata.artist,
+        "ti" => &mut metadata.title,
+        "al" => &mut metadata.album,
+        "length" => {
+            metadata.length = parse_lrc_timestamp(value);
+            return true;
+        }
+        "offset" => {
+            metadata.offset_ms = value.parse().ok();
+            return true;
         }
+        // 其他 [by:] [re:] 等标签不计入歌词，也不算无法解析的行
+        _ => return !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic()),
+    };
+    if !value.is_empty() {
+        *field = Some(value.to_string());
     }
-
-    Ok(None)
+    true
 }
 
-fn from_lrc<P: AsRef<Path>>(path: P) -> anyhow::Result<Lyrics> {
-    let content = fs::read_to_string(&path)?;
+// 去掉逐字时间戳，返回去掉后的文本及是否含有逐字时间戳
+fn strip_word_timestamps(text: &str) -> (String, bool) {
+    let mut result = String::with_capacity(text.len());
+    let mut found = false;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        result.push_str(&rest[..start]);
+        if parse_lrc_timestamp(tag).is_some() {
+            found = true;
+        } else {
+            result.push_str(&rest[start..=start + end]);
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+
+    (result, found)
+}
 
+pub fn parse_lrc(content: &str) -> Lyrics {
+    let mut metadata = LyricsMetadata::default();
     let mut lines = Vec::new();
+    let mut plain_lines = Vec::new();
+    let mut word_timed = false;
+
     for line in content.lines() {
         let line = line.trim();
-        if line.is_empty() {
+        if line.is_empty() || parse_lrc_header(line, &mut metadata) {
             continue;
         }
 
         let mut parts = line.splitn(2, ']');
 
-        if let (Some(time_part), Some(text)) = (parts.next(), parts.next()) {
-            if let Some(timestamp) = parse_lrc_timestamp(time_part) {
-                lines.push(LyricLine {
-                    timestamp,
-                    text: text.trim().to_string(),
-                });
-            }
+        if let (Some(time_part), Some(text)) = (parts.next(), parts.next())
+            && let Some(timestamp) = parse_lrc_timestamp(time_part)
+        {
+            let (text, has_word_timestamps) = strip_word_timestamps(text);
+            word_timed |= has_word_timestamps;
+            lines.push(LyricLine {
+                timestamp,
+                text: text.trim().to_string(),
+                ..LyricLine::default()
+            });
+        } else {
+            plain_lines.push(line);
         }
     }
 
-    Ok(Lyrics { lines })
+    // 没有任何时间戳时按纯文本歌词处理
+    if lines.is_empty() && !plain_lines.is_empty() {
+        let mut lyrics = parse_plain(&plain_lines.join("\n"));
+        lyrics.metadata = metadata;
+        return lyrics;
+    }
+
+    Lyrics {
+        metadata,
+        lines,
+        synced: true,
+        word_timed,
+        invalid_lines: plain_lines.len(),
+        ..Lyrics::default()
+    }
+}
+
+pub fn parse_plain(content: &str) -> Lyrics {
+    let lines = content
+        .lines()
+        .map(|line| LyricLine {
+            timestamp: Duration::ZERO,
+            text: line.trim().to_string(),
+            ..LyricLine::default()
+        })
+        .collect();
+
+    Lyrics {
+        metadata: LyricsMetadata::default(),
+        lines,
+        synced: false,
+        ..Lyrics::default()
+    }
+}
+
+fn format_timestamp(timestamp: Duration) -> String {
+    let centis = timestamp.as_millis() / 10;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
 }
 
-fn find_lrc_file(lyrics_dir: &Path, target_name: &str) -> Option<PathBuf> {
+// 生成 LRC 文本，纯文本歌词不带时间戳
+pub fn format_lrc(lyrics: &Lyrics) -> String {
+    let metadata = &lyrics.metadata;
+    let mut content = String::new();
+    for (tag, value) in [
+        ("ar", &metadata.artist),
+        ("ti", &metadata.title),
+        ("al", &metadata.album),
+    ] {
+        if let Some(value) = value {
+            content.push_str(&format!("[{tag}:{value}]\n"));
+        }
+    }
+    if let Some(length) = metadata.length {
+        content.push_str(&format!("[length:{}]\n", format_timestamp(length)));
+    }
+    if let Some(offset_ms) = metadata.offset_ms {
+        content.push_str(&format!("[offset:{offset_ms}]\n"));
+    }
+
+    for line in &lyrics.lines {
+        if lyrics.synced {
+            content.push_str(&format!("[{}]", format_timestamp(line.timestamp)));
+        }
+        content.push_str(&line.text);
+        content.push('\n');
+    }
+    content
+}
+
+// 替换或插入 [offset:] 标签，其余内容原样保留
+pub fn set_lrc_offset(content: &str, offset_ms: i64) -> String {
+    let tag = format!("[offset:{offset_ms}]");
+    let mut lines: Vec<&str> = content.lines().collect();
+    let existing = lines.iter().position(|line| {
+        line.trim()
+            .strip_prefix('[')
+            .and_then(|s| s.split_once(':'))
+            .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("offset"))
+    });
+    match existing {
+        Some(index) => lines[index] = &tag,
+        None => {
+            // 放在开头的标签之后、第一行歌词之前
+            let index = lines
+                .iter()
+                .position(|line| {
+                    let line = line.trim();
+                    !line.is_empty() && !parse_lrc_header(line, &mut LyricsMetadata::default())
+                })
+                .unwrap_or(lines.len());
+            lines.insert(index, &tag);
+        }
+    }
+
+    // 沿用原文件的换行符
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut result = lines.join(newline);
+    result.push_str(newline);
+    result
+}
+
+pub fn from_audio<P: AsRef<Path>>(path: P) -> anyhow::Result<Option<Lyrics>> {
+    let tagged_file = read_from_path(path)?;
+
+    if let Some(tag) = tagged_file.primary_tag()
+        && let Some(lyrics) = tag.get_string(&lofty::tag::ItemKey::Lyrics)
+    {
+        let lyrics = parse_lrc(lyrics);
+        if !lyrics.lines.is_empty() {
+            return Ok(Some(lyrics));
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn from_lrc<P: AsRef<Path>>(
+    path: P,
+    encoding: Option<&'static Encoding>,
+) -> anyhow::Result<Lyrics> {
+    let bytes = fs::read(&path)?;
+    let content = match encoding {
+        Some(encoding) => encoding.decode(&bytes).0.into_owned(),
+        None => String::from_utf8(bytes)?,
+    };
+    Ok(parse_lrc(&content))
+}
+
+pub fn lrc_files(lyrics_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(lyrics_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lrc"))
+        })
+        .map(|entry| entry.into_path())
+}
+
+pub fn find_lrc_file(lyrics_dir: &Path, target_name: &str) -> Option<PathBuf> {
     let target_filename = format!("{target_name}.lrc");
 
     WalkDir::new(lyrics_dir)
@@ -85,50 +284,220 @@ fn find_lrc_file(lyrics_dir: &Path, target_name: &str) -> Option<PathBuf> {
         .map(|entry| entry.into_path())
 }
 
-fn find_audio_file(music_dir: &Path, target_name: &str) -> Option<PathBuf> {
+pub fn find_audio_file(music_dir: &Path, target_name: &str) -> Option<PathBuf> {
+    let direct = music_dir.join(target_name);
+    if direct.is_file() {
+        return Some(direct);
+    }
+
+    let file_name = Path::new(target_name).file_name()?.to_str()?;
     WalkDir::new(music_dir)
         .into_iter()
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.file_type().is_file())
-        .find(|entry| entry.file_name().to_str() == Some(target_name))
+        .find(|entry| entry.file_name().to_str() == Some(file_name))
         .map(|entry| entry.into_path())
 }
 
-pub fn find_lyrics_for_track(
-    config: &PathConfig,
-    track_name: &str,
-) -> anyhow::Result<Option<Lyrics>> {
-    let base_name = Path::new(track_name)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(track_name);
+fn load_binding(
+    config: &Config,
+    track: &TrackInfo,
+    binding: &LyricsBinding,
+) -> anyhow::Result<Option<LyricsCandidate>> {
+    match binding {
+        LyricsBinding::File { path } => LrcFileProvider::new(config)
+            .candidate(path.clone())
+            .map(Some),
+        LyricsBinding::Embedded => Ok(EmbeddedProvider::new(config)
+            .fetch(track)?
+            .into_iter()
+            .next()),
+    }
+}
 
-    let lyrics_dir = &config.lyrics_dir;
+// 列出当前歌曲所有可选的歌词，内嵌歌词在前，LRC 文件按匹配度排序
+pub fn list_candidates(config: &Config, track: &TrackInfo) -> Vec<LyricsCandidate> {
+    let mut candidates = EmbeddedProvider::new(config)
+        .fetch(track)
+        .unwrap_or_default();
+    candidates.extend(LrcFileProvider::new(config).all_candidates(track));
+    candidates
+}
 
-    let lrc_file = find_lrc_file(lyrics_dir, base_name);
+pub fn bind_lyrics(track: &TrackInfo, binding: LyricsBinding) -> anyhow::Result<()> {
+    let path = get_mapping_path();
+    let mut mapping = LyricsMapping::load(&path)?;
+    mapping.bind(&track.file, binding);
+    mapping.save(&path)
+}
 
-    if let Some(file) = lrc_file {
-        let lyrics = from_lrc(file)?;
-        return Ok(Some(lyrics));
+// 返回按质量排序的候选歌词，第一个即为使用的歌词
+pub fn find_lyrics_for_track(
+    config: &Config,
+    mapping: &LyricsMapping,
+    providers: &[Box<dyn LyricsProvider>],
+    track: &TrackInfo,
+) -> Vec<ScoredCandidate> {
+    // 手动绑定的歌词优先于任何搜索
+    if let Some(binding) = mapping.get(&track.file)
+        && let Some(candidate) = load_binding(config, track, binding).ok().flatten()
+    {
+        let mut ranking = rank_candidates(track, [candidate]);
+        attach_translations(config, track, &mut ranking[0].candidate);
+        return ranking;
     }
 
-    let music_dir = &config.music_dir;
-
-    let audio_file = find_audio_file(music_dir, track_name);
-    if let Some(file) = audio_file {
-        let lyrics = from_audio(file)?;
-        return Ok(lyrics);
+    let mut ranking = search_providers(providers, track, config.lyrics.local_first);
+    if let Some(best) = ranking.first_mut() {
+        attach_translations(config, track, &mut best.candidate);
     }
+    ranking
+}
 
-    Ok(None)
+// 按配置顺序查询所有来源并一起排序，得分相同时靠前的来源优先；
+// 开启 local_first 时本地来源已有合适的同步歌词就不再查询在线来源
+fn search_providers(
+    providers: &[Box<dyn LyricsProvider>],
+    track: &TrackInfo,
+    local_first: bool,
+) -> Vec<ScoredCandidate> {
+    // 单个来源出错（如外部命令失败）时继续尝试其他来源
+    let fetch = |provider: &dyn LyricsProvider| provider.fetch(track).unwrap_or_default();
+
+    let mut found: Vec<Vec<LyricsCandidate>> = providers
+        .iter()
+        .map(|p| {
+            if local_first && p.is_remote() {
+                Vec::new()
+            } else {
+                fetch(p.as_ref())
+            }
+        })
+        .collect();
+    let local_good = found
+        .iter()
+        .flatten()
+        .any(|c| score_lyrics(track, &c.lyrics).is_good());
+    if local_first && !local_good {
+        for (provider, candidates) in providers.iter().zip(&mut found) {
+            if provider.is_remote() {
+                *candidates = fetch(provider.as_ref());
+            }
+        }
+    }
+    rank_candidates(track, found.into_iter().flatten())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::path::Path;
+    use std::{fs, path::Path};
 
-    use crate::lyrics::parser::{find_audio_file, find_lrc_file, from_audio, from_lrc};
+    use tempfile::tempdir;
+
+    use crate::{
+        lyrics::{
+            mapping::{LyricsBinding, LyricsMapping},
+            parser::{
+                find_audio_file, find_lrc_file, find_lyrics_for_track, format_lrc, from_audio,
+                from_lrc, parse_lrc, search_providers, set_lrc_offset,
+            },
+            provider::{LyricsCandidate, LyricsProvider},
+        },
+        model::{config::Config, track::TrackInfo},
+    };
+
+    struct StaticProvider {
+        label: &'static str,
+        content: &'static str,
+        remote: bool,
+    }
+
+    impl StaticProvider {
+        fn new(label: &'static str, content: &'static str, remote: bool) -> Box<Self> {
+            Box::new(Self {
+                label,
+                content,
+                remote,
+            })
+        }
+    }
+
+    impl LyricsProvider for StaticProvider {
+        fn name(&self) -> &'static str {
+            "static"
+        }
+
+        fn fetch(&self, _track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+            Ok(vec![LyricsCandidate {
+                source: "static",
+                label: self.label.into(),
+                binding: None,
+                lyrics: parse_lrc(self.content),
+            }])
+        }
+
+        fn is_remote(&self) -> bool {
+            self.remote
+        }
+    }
+
+    fn labels(providers: &[Box<dyn LyricsProvider>], local_first: bool) -> Vec<String> {
+        search_providers(providers, &TrackInfo::default(), local_first)
+            .into_iter()
+            .map(|s| s.candidate.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_search_providers() {
+        let providers: Vec<Box<dyn LyricsProvider>> = vec![
+            StaticProvider::new("remote", "[00:01.00]a", true),
+            StaticProvider::new("plain", "a\nb", false),
+            StaticProvider::new("line", "[00:01.00]a", false),
+            StaticProvider::new("word", "[00:01.00]<00:01.00>a", false),
+        ];
+        // 所有来源一起排序，得分相同时按配置顺序
+        assert_eq!(
+            labels(&providers, false),
+            ["word", "remote", "line", "plain"]
+        );
+        // 本地已有合适的同步歌词时不查询在线来源
+        assert_eq!(labels(&providers, true), ["word", "line", "plain"]);
+
+        let providers: Vec<Box<dyn LyricsProvider>> = vec![
+            StaticProvider::new("plain", "a\nb", false),
+            StaticProvider::new("remote", "[00:01.00]a", true),
+        ];
+        assert_eq!(labels(&providers, true), ["remote", "plain"]);
+    }
+
+    #[test]
+    fn test_find_lyrics_bound_file() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.paths.lyrics_dir = dir.path().to_path_buf();
+        let path = dir.path().join("bound.lrc");
+        fs::write(&path, "[00:01.00]a").unwrap();
+
+        let track = TrackInfo {
+            file: "song.flac".into(),
+            ..Default::default()
+        };
+        let providers: Vec<Box<dyn LyricsProvider>> =
+            vec![StaticProvider::new("word", "[00:01.00]<00:01.00>a", false)];
+        let first = |mapping: &LyricsMapping| {
+            find_lyrics_for_track(&config, mapping, &providers, &track)[0]
+                .candidate
+                .label
+                .clone()
+        };
+        assert_eq!(first(&LyricsMapping::default()), "word");
+
+        let mut mapping = LyricsMapping::default();
+        mapping.bind(&track.file, LyricsBinding::File { path });
+        assert_eq!(first(&mapping), "bound.lrc");
+    }
 
     #[test]
     fn test_audio_lyrics() {
@@ -139,12 +508,70 @@ mod tests {
 
     #[test]
     fn get_lyrics_by_lrc() {
-        let result = from_lrc("test_data/完美借口_歌词.lrc");
+        let result = from_lrc("test_data/完美借口_歌词.lrc", None);
         assert!(result.is_ok(), "应该成功解析LRC文件");
         let lyrics = result.unwrap();
         assert!(!lyrics.lines.is_empty(), "歌词不应为空");
     }
 
+    #[test]
+    fn test_parse_lrc_headers() {
+        let lyrics = parse_lrc("[ar:歌手]\n[ti:标题]\n[al:专辑]\n[length:03:25]\n[00:01.00]第一句");
+        assert_eq!(lyrics.metadata.artist.as_deref(), Some("歌手"));
+        assert_eq!(lyrics.metadata.title.as_deref(), Some("标题"));
+        assert_eq!(lyrics.metadata.album.as_deref(), Some("专辑"));
+        assert_eq!(lyrics.metadata.length.map(|d| d.as_secs()), Some(205));
+        assert_eq!(lyrics.lines.len(), 1);
+        assert!(lyrics.synced);
+    }
+
+    #[test]
+    fn test_parse_lrc_timestamp_rounding() {
+        // 2.03 * 1000 为 2029.999...，截断会少 1 毫秒
+        let lyrics = parse_lrc("[01:02.03]第一句");
+        assert_eq!(lyrics.lines[0].timestamp.as_millis(), 62030);
+    }
+
+    #[test]
+    fn test_parse_plain_lyrics() {
+        let lyrics = parse_lrc("[ti:标题]\n第一句\n\n第二句");
+        assert!(!lyrics.synced);
+        assert_eq!(lyrics.metadata.title.as_deref(), Some("标题"));
+        assert_eq!(lyrics.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_format_lrc() {
+        let content =
+            "[ar:歌手]\n[ti:标题]\n[length:03:25.00]\n[00:01.50]第一句\n[01:02.03]第二句\n";
+        assert_eq!(format_lrc(&parse_lrc(content)), content);
+        assert_eq!(format_lrc(&parse_lrc("第一句\n第二句")), "第一句\n第二句\n");
+    }
+
+    #[test]
+    fn test_set_lrc_offset() {
+        let content = "[ar:歌手]\n[ti:标题]\n[00:01.50]第一句\n";
+        let updated = set_lrc_offset(content, 300);
+        assert_eq!(
+            updated,
+            "[ar:歌手]\n[ti:标题]\n[offset:300]\n[00:01.50]第一句\n"
+        );
+        assert_eq!(parse_lrc(&updated).metadata.offset_ms, Some(300));
+
+        let updated = set_lrc_offset(&updated, -200);
+        assert_eq!(
+            updated,
+            "[ar:歌手]\n[ti:标题]\n[offset:-200]\n[00:01.50]第一句\n"
+        );
+        assert_eq!(
+            parse_lrc("[offset:+500]\n[00:01.00]a").metadata.offset_ms,
+            Some(500)
+        );
+
+        let updated = set_lrc_offset("[ti:标题]\r\n[00:01.50]第一句\r\n", 100);
+        assert_eq!(updated, "[ti:标题]\r\n[offset:100]\r\n[00:01.50]第一句\r\n");
+    }
+
     #[test]
     fn test_find_lrc_file() {
         let path = Path::new("test_data");
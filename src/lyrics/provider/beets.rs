@@ -0,0 +1,253 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row};
+
+use crate::{
+    lyrics::{
+        parser::parse_lrc,
+        provider::{LyricsCandidate, LyricsProvider, lrc::LrcFileProvider},
+    },
+    model::{config::Config, lyrics::Lyrics, track::TrackInfo},
+};
+
+pub const NAME: &str = "beets";
+
+const ITEM_COLUMNS: &str = "artist, title, album, length, lyrics";
+
+#[derive(Debug, Default, PartialEq)]
+struct BeetsItem {
+    artist: Option<String>,
+    title: Option<String>,
+    album: Option<String>,
+    length: Option<f64>,
+    lyrics: Option<String>,
+}
+
+pub struct BeetsProvider {
+    library: PathBuf,
+    music_dir: PathBuf,
+    lrc: LrcFileProvider,
+}
+
+impl BeetsProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            library: config.lyrics.beets.library.clone(),
+            music_dir: config.paths.music_dir.clone(),
+            lrc: LrcFileProvider::new(config),
+        }
+    }
+
+    // 只读打开，避免与正在运行的 beets 争用写锁
+    fn open(&self) -> anyhow::Result<Connection> {
+        Connection::open_with_flags(
+            &self.library,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("打开 beets 数据库失败: {}", self.library.display()))
+    }
+
+    // beets 保存绝对路径，先按音乐目录拼接查找，找不到再按路径后缀匹配唯一条目
+    fn find_item(&self, track: &TrackInfo) -> anyhow::Result<Option<BeetsItem>> {
+        let conn = self.open()?;
+
+        let full_path = self.music_dir.join(&track.file);
+        let item = conn
+            .query_row(
+                &format!("SELECT {ITEM_COLUMNS} FROM items WHERE path = ?1"),
+                [path_bytes(&full_path)],
+                read_item,
+            )
+            .optional()?;
+        if item.is_some() {
+            return Ok(item);
+        }
+
+        let suffix = format!("/{}", track.file).into_bytes();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE substr(path, -?1) = ?2 LIMIT 2"
+        ))?;
+        let mut items = stmt
+            .query_map((suffix.len() as i64, &suffix), read_item)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((items.len() == 1).then(|| items.remove(0)))
+    }
+}
+
+// beets 以原始字节保存路径
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+fn read_item(row: &Row) -> rusqlite::Result<BeetsItem> {
+    let text = |index: usize| -> rusqlite::Result<Option<String>> {
+        Ok(row
+            .get::<_, Option<String>>(index)?
+            .filter(|s| !s.trim().is_empty()))
+    };
+    Ok(BeetsItem {
+        artist: text(0)?,
+        title: text(1)?,
+        album: text(2)?,
+        length: row.get(3)?,
+        lyrics: text(4)?,
+    })
+}
+
+impl BeetsItem {
+    // 用 beets 整理过的标签覆盖 MPD 标签，缺失的字段保留原值
+    fn apply_to(&self, track: &TrackInfo) -> TrackInfo {
+        TrackInfo {
+            file: track.file.clone(),
+            artist: self.artist.clone().or_else(|| track.artist.clone()),
+            title: self.title.clone().or_else(|| track.title.clone()),
+            album: self.album.clone().or_else(|| track.album.clone()),
+            duration: self
+                .length
+                .filter(|l| *l > 0.0)
+                .map(Duration::from_secs_f64)
+                .or(track.duration),
+            stream: track.stream,
+        }
+    }
+
+    fn lyrics(&self) -> Option<Lyrics> {
+        let lyrics = parse_lrc(self.lyrics.as_deref()?);
+        (!lyrics.lines.is_empty()).then_some(lyrics)
+    }
+}
+
+impl LyricsProvider for BeetsProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        let Some(item) = self.find_item(track)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut candidates: Vec<LyricsCandidate> = item
+            .lyrics()
+            .map(|lyrics| LyricsCandidate {
+                source: NAME,
+                label: "beets 歌词字段".to_string(),
+                binding: None,
+                lyrics,
+            })
+            .into_iter()
+            .collect();
+
+        // 用 beets 的标签重新匹配外部 LRC 文件
+        let tagged = item.apply_to(track);
+        let files = self.lrc.fetch(&tagged)?;
+        if files.is_empty() {
+            candidates.extend(self.lrc.find_by_tags(&tagged));
+        } else {
+            candidates.extend(files);
+        }
+        Ok(candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::{TempDir, tempdir};
+
+    use super::*;
+
+    fn library(items: &[(&str, &str, &str, Option<&str>)]) -> TempDir {
+        let dir = tempdir().unwrap();
+        let conn = Connection::open(dir.path().join("library.db")).unwrap();
+        conn.execute(
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, path BLOB, artist TEXT, title TEXT,
+             album TEXT, length REAL, lyrics TEXT)",
+            [],
+        )
+        .unwrap();
+        for (path, artist, title, lyrics) in items {
+            conn.execute(
+                "INSERT INTO items (path, artist, title, album, length, lyrics)
+                 VALUES (?1, ?2, ?3, '', 200.5, ?4)",
+                (path.as_bytes(), artist, title, lyrics),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    fn provider(dir: &TempDir) -> BeetsProvider {
+        let mut config = Config::default();
+        config.paths.music_dir = PathBuf::from("/music");
+        config.paths.lyrics_dir = PathBuf::from("test_data");
+        config.lyrics.match_threshold = 0.5;
+        config.lyrics.beets.library = dir.path().join("library.db");
+        BeetsProvider::new(&config)
+    }
+
+    #[test]
+    fn test_lyrics_field_and_tags() {
+        let dir = library(&[(
+            "/music/Artist/01 track.flac",
+            "Artist",
+            "Song",
+            Some("[00:01.00]first\n[00:02.00]second"),
+        )]);
+        let provider = provider(&dir);
+        let track = TrackInfo {
+            file: "Artist/01 track.flac".into(),
+            ..Default::default()
+        };
+
+        let item = provider.find_item(&track).unwrap().unwrap();
+        let enriched = item.apply_to(&track);
+        assert_eq!(enriched.title.as_deref(), Some("Song"));
+        assert_eq!(enriched.album, None);
+        assert_eq!(enriched.duration, Some(Duration::from_secs_f64(200.5)));
+
+        let candidates = provider.fetch(&track).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].source, NAME);
+        assert!(candidates[0].lyrics.synced);
+    }
+
+    #[test]
+    fn test_match_lrc_by_path_suffix() {
+        // beets 的音乐目录与 lyra 配置不同时按路径后缀匹配
+        let dir = library(&[
+            (
+                "/srv/beets/小师妹/01 track.flac",
+                "小师妹",
+                "完美借口",
+                None,
+            ),
+            ("/srv/beets/Other/02 track.flac", "Other", "Tune", None),
+        ]);
+        let provider = provider(&dir);
+        let track = TrackInfo {
+            file: "小师妹/01 track.flac".into(),
+            ..Default::default()
+        };
+
+        let candidates = provider.fetch(&track).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].label, "完美借口_歌词.lrc");
+
+        let unknown = TrackInfo {
+            file: "Unknown/01 track.flac".into(),
+            ..Default::default()
+        };
+        assert!(provider.fetch(&unknown).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{Mutex, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, bail};
+
+use crate::{
+    lyrics::{
+        parser::parse_lrc,
+        provider::{LyricsCandidate, LyricsProvider},
+    },
+    model::{
+        config::{CommandProviderConfig, Config},
+        lyrics::Lyrics,
+        track::TrackInfo,
+    },
+};
+
+pub const NAME: &str = "command";
+
+// 歌曲路径，电台流另加当前的歌手和标题
+type CacheKey = (String, Option<String>, Option<String>);
+
+fn cache_key(track: &TrackInfo) -> CacheKey {
+    if track.stream {
+        (
+            track.file.clone(),
+            track.artist.clone(),
+            track.title.clone(),
+        )
+    } else {
+        (track.file.clone(), None, None)
+    }
+}
+
+pub struct CommandProvider {
+    config: CommandProviderConfig,
+    music_dir: PathBuf,
+    cache: Mutex<HashMap<CacheKey, Option<Lyrics>>>,
+}
+
+impl CommandProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.lyrics.command.clone(),
+            music_dir: config.paths.music_dir.clone(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn variables(&self, track: &TrackInfo) -> Vec<(&'static str, String)> {
+        vec![
+            ("path", track.file.clone()),
+            (
+                "file",
+                self.music_dir
+                    .join(&track.file)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            ("artist", track.artist.clone().unwrap_or_default()),
+            ("title", track.title.clone().unwrap_or_default()),
+            ("album", track.album.clone().unwrap_or_default()),
+            (
+                "duration",
+                track
+                    .duration
+                    .map(|d| d.as_secs().to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn run(&self, track: &TrackInfo) -> anyhow::Result<Option<Lyrics>> {
+        if self.config.program.is_empty() {
+            bail!("未配置歌词命令");
+        }
+
+        let variables = self.variables(track);
+        let args = self.config.args.iter().map(|arg| {
+            variables.iter().fold(arg.clone(), |arg, (key, value)| {
+                arg.replace(&format!("{{{key}}}"), value)
+            })
+        });
+
+        let mut command = Command::new(&self.config.program);
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .args(args)
+            .envs(
+                variables
+                    .iter()
+                    .map(|(key, value)| (format!("LYRA_{}", key.to_uppercase()), value)),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("启动歌词命令失败: {}", self.config.program))?;
+
+        let mut stdout = child.stdout.take().context("无法读取歌词命令输出")?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = sender.send(stdout.read_to_string(&mut output).map(|_| output));
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(self.config.timeout_secs);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                kill_process_group(&mut child);
+                bail!("歌词命令超时: {}", self.config.program);
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+
+        // 命令已退出，但它启动的后台进程可能仍占用输出管道
+        let output = match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        {
+            Ok(output) => output.context("读取歌词命令输出失败")?,
+            Err(_) => {
+                kill_process_group(&mut child);
+                bail!("歌词命令超时: {}", self.config.program);
+            }
+        };
+        if !status.success() {
+            bail!("歌词命令执行失败: {status}");
+        }
+
+        let lyrics = parse_lrc(&output);
+        Ok((!lyrics.lines.is_empty()).then_some(lyrics))
+    }
+}
+
+// 命令在独立的进程组中运行，超时后连同它启动的子进程一起结束
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+impl LyricsProvider for CommandProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        let key = cache_key(track);
+        let cached = self.cache.lock().unwrap().get(&key).cloned();
+        let lyrics = match cached {
+            Some(lyrics) => lyrics,
+            None => {
+                let lyrics = self.run(track)?;
+                self.cache.lock().unwrap().insert(key, lyrics.clone());
+                lyrics
+            }
+        };
+
+        Ok(lyrics
+            .map(|lyrics| LyricsCandidate {
+                source: NAME,
+                label: self.config.program.clone(),
+                binding: None,
+                lyrics,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn provider(script: &str, timeout_secs: u64) -> CommandProvider {
+        CommandProvider {
+            config: CommandProviderConfig {
+                program: "sh".into(),
+                args: vec!["-c".into(), script.into(), "{title}".into()],
+                timeout_secs,
+            },
+            music_dir: PathBuf::from("/music"),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            file: "Artist/Song.flac".into(),
+            artist: Some("Artist".into()),
+            title: Some("Song".into()),
+            album: Some("Album".into()),
+            duration: Some(Duration::from_secs(200)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fetch_from_command_output() {
+        let provider = provider(
+            r#"printf '[00:01.00]%s\n[00:02.00]%s - %s\n' "$0" "$LYRA_ARTIST" "$LYRA_FILE""#,
+            5,
+        );
+        let candidates = provider.fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+
+        let lines = &candidates[0].lyrics.lines;
+        assert_eq!(lines[0].text, "Song");
+        assert_eq!(lines[1].text, "Artist - /music/Artist/Song.flac");
+    }
+
+    #[test]
+    fn test_empty_output_yields_nothing() {
+        let provider = provider("exit 0", 5);
+        assert!(provider.fetch(&track()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failing_command() {
+        let provider = provider("exit 3", 5);
+        assert!(provider.fetch(&track()).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let provider = provider("sleep 5", 1);
+        let started = Instant::now();
+        assert!(provider.fetch(&track()).is_err());
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_timeout_with_background_child() {
+        // 后台进程继承了标准输出，管道在命令退出后仍未关闭
+        let provider = provider("sleep 5 & echo '[00:01.00]line'", 1);
+        let started = Instant::now();
+        assert!(provider.fetch(&track()).is_err());
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_results_are_cached() {
+        let dir = tempdir().unwrap();
+        let counter = dir.path().join("count");
+        let provider = provider(
+            &format!("echo x >> '{}'; echo '[00:01.00]line'", counter.display()),
+            5,
+        );
+
+        provider.fetch(&track()).unwrap();
+        provider.fetch(&track()).unwrap();
+        let runs = std::fs::read_to_string(&counter).unwrap();
+        assert_eq!(runs.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_stream_cached_per_title() {
+        let provider = provider(r#"echo "[00:01.00]$0""#, 5);
+        let stream = |title: &str| TrackInfo {
+            file: "http://radio.example.com/live".into(),
+            artist: Some("Singer".into()),
+            title: Some(title.into()),
+            stream: true,
+            ..Default::default()
+        };
+
+        // 同一电台地址的不同歌曲不共用缓存
+        for title in ["Song A", "Song B", "Song A"] {
+            let candidates = provider.fetch(&stream(title)).unwrap();
+            assert_eq!(candidates[0].lyrics.lines[0].text, title);
+        }
+    }
+}
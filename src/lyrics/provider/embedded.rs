@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use crate::{
+    lyrics::{
+        mapping::LyricsBinding,
+        parser::{find_audio_file, from_audio},
+        provider::{LyricsCandidate, LyricsProvider},
+    },
+    model::{config::Config, track::TrackInfo},
+};
+
+pub const NAME: &str = "embedded";
+
+pub struct EmbeddedProvider {
+    music_dir: PathBuf,
+}
+
+impl EmbeddedProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            music_dir: config.paths.music_dir.clone(),
+        }
+    }
+}
+
+impl LyricsProvider for EmbeddedProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        let Some(file) = find_audio_file(&self.music_dir, &track.file) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(from_audio(file)?
+            .map(|lyrics| LyricsCandidate {
+                source: NAME,
+                label: "内嵌歌词".to_string(),
+                binding: Some(LyricsBinding::Embedded),
+                lyrics,
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_embedded_lyrics() {
+        let provider = EmbeddedProvider {
+            music_dir: PathBuf::from("test_data"),
+        };
+        let track = TrackInfo {
+            file: "1个球 - 大雨还在下.mp3".into(),
+            ..Default::default()
+        };
+        let candidates = provider.fetch(&track).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].source, NAME);
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use encoding_rs::Encoding;
+
+use crate::{
+    lyrics::{
+        mapping::LyricsBinding,
+        matcher::{MatchCandidate, find_best_match, score},
+        parser::{find_lrc_file, from_lrc, lrc_files},
+        provider::{LyricsCandidate, LyricsProvider},
+        translation::is_translation_file,
+    },
+    model::{config::Config, track::TrackInfo},
+};
+
+pub const NAME: &str = "lrc";
+pub const FUZZY_NAME: &str = "lrc-fuzzy";
+
+pub struct LrcFileProvider {
+    lyrics_dir: PathBuf,
+    match_threshold: f64,
+    encoding: Option<&'static Encoding>,
+    translation_suffixes: Vec<String>,
+}
+
+impl LrcFileProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            lyrics_dir: config.paths.lyrics_dir.clone(),
+            match_threshold: config.lyrics.match_threshold,
+            encoding: Encoding::for_label(config.lyrics.encoding.as_bytes()),
+            translation_suffixes: config.lyrics.translation_suffixes.clone(),
+        }
+    }
+
+    pub fn candidate(&self, path: PathBuf) -> anyhow::Result<LyricsCandidate> {
+        let lyrics = from_lrc(&path, self.encoding)?;
+        let label = path
+            .strip_prefix(&self.lyrics_dir)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .into_owned();
+        Ok(LyricsCandidate {
+            source: NAME,
+            label,
+            binding: Some(LyricsBinding::File { path }),
+            lyrics,
+        })
+    }
+
+    fn index(&self) -> Vec<(MatchCandidate, LyricsCandidate)> {
+        lrc_files(&self.lyrics_dir)
+            .filter(|path| !is_translation_file(path, &self.translation_suffixes))
+            .filter_map(|path| {
+                let candidate = self.candidate(path.clone()).ok()?;
+                let metadata = &candidate.lyrics.metadata;
+                let matcher =
+                    MatchCandidate::new(path, metadata.artist.clone(), metadata.title.clone());
+                Some((matcher, candidate))
+            })
+            .collect()
+    }
+
+    // 列出目录下所有 LRC 文件，按与当前歌曲的匹配度排序
+    pub fn all_candidates(&self, track: &TrackInfo) -> Vec<LyricsCandidate> {
+        let mut scored: Vec<(f64, LyricsCandidate)> = self
+            .index()
+            .into_iter()
+            .map(|(matcher, candidate)| (score(track, &matcher), candidate))
+            .collect();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+
+    // 用 MPD 的歌手/标题标签模糊匹配 LRC 头部或文件名
+    pub fn find_by_tags(&self, track: &TrackInfo) -> Option<LyricsCandidate> {
+        find_best_match(track, self.index(), self.match_threshold)
+    }
+}
+
+impl LyricsProvider for LrcFileProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        // 电台流的地址不对应某一首歌，只能按标签模糊匹配
+        if track.stream {
+            return Ok(Vec::new());
+        }
+        let base_name = Path::new(&track.file)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&track.file);
+
+        match find_lrc_file(&self.lyrics_dir, base_name) {
+            Some(file) => Ok(vec![self.candidate(file)?]),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+// 模糊匹配单独作为一个来源，默认排在内嵌歌词之后，避免猜测的结果盖过确定的歌词
+pub struct LrcFuzzyProvider {
+    lrc: LrcFileProvider,
+}
+
+impl LrcFuzzyProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            lrc: LrcFileProvider::new(config),
+        }
+    }
+}
+
+impl LyricsProvider for LrcFuzzyProvider {
+    fn name(&self) -> &'static str {
+        FUZZY_NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        Ok(self
+            .lrc
+            .find_by_tags(track)
+            .map(|candidate| LyricsCandidate {
+                source: FUZZY_NAME,
+                ..candidate
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(match_threshold: f64) -> LrcFileProvider {
+        LrcFileProvider {
+            lyrics_dir: PathBuf::from("test_data"),
+            match_threshold,
+            encoding: None,
+            translation_suffixes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fetch_by_file_name() {
+        let track = TrackInfo {
+            file: "somewhere/完美借口_歌词.flac".into(),
+            ..Default::default()
+        };
+        let candidates = provider(0.8).fetch(&track).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].label, "完美借口_歌词.lrc");
+
+        // 电台地址中的文件名不代表歌曲
+        let stream = TrackInfo {
+            file: "http://radio.example.com/完美借口_歌词.mp3".into(),
+            stream: true,
+            ..Default::default()
+        };
+        assert!(provider(0.8).fetch(&stream).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fetch_by_tags() {
+        let track = TrackInfo {
+            file: "小师妹/01 track.flac".into(),
+            artist: Some("小师妹".into()),
+            title: Some("完美借口 (Live)".into()),
+            ..Default::default()
+        };
+        // 按文件名查找时不做模糊匹配
+        assert!(provider(0.5).fetch(&track).unwrap().is_empty());
+
+        let fuzzy = |match_threshold| LrcFuzzyProvider {
+            lrc: provider(match_threshold),
+        };
+        let candidates = fuzzy(0.5).fetch(&track).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].source, FUZZY_NAME);
+
+        let track = TrackInfo {
+            title: Some("毫不相干的歌".into()),
+            ..track
+        };
+        assert!(fuzzy(0.8).fetch(&track).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_forced_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.lrc");
+        let (bytes, _, _) = encoding_rs::GBK.encode("[00:01.00]大雨还在下");
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut provider = LrcFileProvider {
+            lyrics_dir: dir.path().to_path_buf(),
+            match_threshold: 0.8,
+            encoding: None,
+            translation_suffixes: Vec::new(),
+        };
+        assert!(provider.candidate(path.clone()).is_err());
+
+        provider.encoding = Encoding::for_label(b"gbk");
+        let candidate = provider.candidate(path).unwrap();
+        assert_eq!(candidate.lyrics.lines[0].text, "大雨还在下");
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use ureq::Agent;
+
+use crate::{
+    lyrics::{
+        mapping::LyricsBinding,
+        parser::parse_lrc,
+        provider::{LyricsCandidate, LyricsProvider, http_agent, sanitize_file_name},
+    },
+    model::{
+        config::{Config, LrclibConfig, LrclibSaveTarget},
+        track::TrackInfo,
+    },
+};
+
+pub const NAME: &str = "lrclib";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibResponse {
+    synced_lyrics: Option<String>,
+    plain_lyrics: Option<String>,
+}
+
+pub struct LrclibProvider {
+    config: LrclibConfig,
+    music_dir: PathBuf,
+    agent: Agent,
+}
+
+impl LrclibProvider {
+    pub fn new(config: &Config) -> Self {
+        let lrclib = config.lyrics.lrclib.clone();
+        Self {
+            agent: http_agent(lrclib.timeout_secs),
+            config: lrclib,
+            music_dir: config.paths.music_dir.clone(),
+        }
+    }
+
+    fn save_path(&self, track: &TrackInfo) -> Option<PathBuf> {
+        match self.config.save_to {
+            LrclibSaveTarget::Cache => {
+                let artist = track.artist.as_deref().unwrap_or_default();
+                let title = track.title.as_deref()?;
+                let name = sanitize_file_name(&format!("{artist} - {title}"));
+                Some(self.config.cache_dir.join(format!("{name}.lrc")))
+            }
+            LrclibSaveTarget::Track => Some(self.music_dir.join(&track.file).with_extension("lrc")),
+        }
+    }
+
+    fn request(&self, track: &TrackInfo) -> anyhow::Result<Option<String>> {
+        let (Some(artist), Some(title)) = (&track.artist, &track.title) else {
+            return Ok(None);
+        };
+
+        let url = format!("{}/api/get", self.config.base_url.trim_end_matches('/'));
+        let mut request = self
+            .agent
+            .get(&url)
+            .query("artist_name", artist)
+            .query("track_name", title);
+        if let Some(album) = &track.album {
+            request = request.query("album_name", album);
+        }
+        if let Some(duration) = track.duration {
+            request = request.query("duration", duration.as_secs().to_string());
+        }
+
+        let mut response = request.call()?;
+        match response.status().as_u16() {
+            200 => {}
+            404 => return Ok(None),
+            status => anyhow::bail!("LRCLIB 请求失败: HTTP {status}"),
+        }
+
+        let body: LrclibResponse = response.body_mut().read_json()?;
+        Ok(body
+            .synced_lyrics
+            .filter(|s| !s.trim().is_empty())
+            .or(body.plain_lyrics.filter(|s| !s.trim().is_empty())))
+    }
+}
+
+fn save_lyrics(path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+impl LyricsProvider for LrclibProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        let save_path = self.save_path(track);
+
+        let content = match save_path.as_ref().filter(|p| p.is_file()) {
+            Some(path) => fs::read_to_string(path)?,
+            None => {
+                let Some(content) = self.request(track)? else {
+                    return Ok(Vec::new());
+                };
+                if let Some(path) = &save_path {
+                    save_lyrics(path, &content)?;
+                }
+                content
+            }
+        };
+
+        let lyrics = parse_lrc(&content);
+        if lyrics.lines.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![LyricsCandidate {
+            source: NAME,
+            label: save_path
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.config.base_url.clone()),
+            binding: save_path.map(|path| LyricsBinding::File { path }),
+            lyrics,
+        }])
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::lyrics::provider::mock_http;
+
+    fn mock_server(
+        status: &'static str,
+        body: &str,
+    ) -> (String, std::sync::mpsc::Receiver<String>) {
+        mock_http::serve(vec![("/api/get", status, body.to_string())])
+    }
+
+    fn provider(base_url: String, cache_dir: &Path) -> LrclibProvider {
+        let mut config = Config::default();
+        config.lyrics.lrclib.base_url = base_url;
+        config.lyrics.lrclib.cache_dir = cache_dir.to_path_buf();
+        LrclibProvider::new(&config)
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            file: "Artist/Album/Song.flac".into(),
+            artist: Some("Artist".into()),
+            title: Some("Song Title".into()),
+            album: Some("Album".into()),
+            duration: Some(Duration::from_secs(215)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fetch_synced_lyrics_and_cache() {
+        let (base_url, requests) = mock_server(
+            "200 OK",
+            r#"{"syncedLyrics":"[00:01.00]first\n[00:02.00]second","plainLyrics":"first\nsecond"}"#,
+        );
+        let cache = tempdir().unwrap();
+        let provider = provider(base_url, cache.path());
+
+        let candidates = provider.fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].lyrics.synced);
+        assert_eq!(candidates[0].lyrics.lines.len(), 2);
+
+        let request_line = requests.recv().unwrap();
+        assert!(request_line.starts_with("GET /api/get?"));
+        assert!(request_line.contains("artist_name=Artist"));
+        assert!(request_line.contains("track_name=Song"));
+        assert!(request_line.contains("album_name=Album"));
+        assert!(request_line.contains("duration=215"));
+
+        // 第二次直接读取缓存，不再请求服务器
+        let cached = cache.path().join("Artist - Song Title.lrc");
+        assert!(cached.is_file());
+        let candidates = provider.fetch(&track()).unwrap();
+        assert_eq!(candidates[0].lyrics.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_fetch_plain_lyrics_fallback() {
+        let (base_url, _requests) = mock_server(
+            "200 OK",
+            r#"{"syncedLyrics":null,"plainLyrics":"first\nsecond"}"#,
+        );
+        let cache = tempdir().unwrap();
+        let candidates = provider(base_url, cache.path()).fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert!(!candidates[0].lyrics.synced);
+    }
+
+    #[test]
+    fn test_fetch_not_found() {
+        let (base_url, _requests) = mock_server("404 Not Found", r#"{"code":404}"#);
+        let cache = tempdir().unwrap();
+        let candidates = provider(base_url, cache.path()).fetch(&track()).unwrap();
+        assert!(candidates.is_empty());
+        assert!(fs::read_dir(cache.path()).unwrap().next().is_none());
+    }
+}
@@ -0,0 +1,127 @@
+pub mod beets;
+pub mod command;
+pub mod embedded;
+pub mod lrc;
+pub mod lrclib;
+#[cfg(test)]
+mod mock_http;
+pub mod mpd;
+pub mod subsonic;
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use ureq::Agent;
+
+use crate::{
+    lyrics::mapping::LyricsBinding,
+    model::{config::Config, lyrics::Lyrics, track::TrackInfo},
+};
+
+#[derive(Debug, Clone)]
+pub struct LyricsCandidate {
+    pub source: &'static str,
+    pub label: String,
+    pub binding: Option<LyricsBinding>,
+    pub lyrics: Lyrics,
+}
+
+pub trait LyricsProvider: Send + Sync {
+    // 配置中使用的来源名称
+    fn name(&self) -> &'static str;
+
+    // 按可信度从高到低返回候选歌词
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>>;
+
+    // 需要网络请求或启动外部进程的来源，只在本地来源没有合适歌词时查询
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Debug for dyn LyricsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LyricsProvider").finish_non_exhaustive()
+    }
+}
+
+// 各来源在程序运行期间只创建一次，以便保留各自的缓存
+pub type Providers = Arc<Vec<Box<dyn LyricsProvider>>>;
+
+// 在线来源共用的 HTTP 客户端配置，非 2xx 状态码由各来源自行处理
+pub fn http_agent(timeout_secs: u64) -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(timeout_secs)))
+        .http_status_as_error(false)
+        .user_agent(concat!("lyra-rs/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into()
+}
+
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+pub fn create_provider(name: &str, config: &Config) -> Option<Box<dyn LyricsProvider>> {
+    match name {
+        lrc::NAME => Some(Box::new(lrc::LrcFileProvider::new(config))),
+        lrc::FUZZY_NAME => Some(Box::new(lrc::LrcFuzzyProvider::new(config))),
+        embedded::NAME => Some(Box::new(embedded::EmbeddedProvider::new(config))),
+        command::NAME => Some(Box::new(command::CommandProvider::new(config))),
+        lrclib::NAME => Some(Box::new(lrclib::LrclibProvider::new(config))),
+        mpd::NAME => Some(Box::new(mpd::MpdCommentsProvider::new(config))),
+        beets::NAME => Some(Box::new(beets::BeetsProvider::new(config))),
+        subsonic::NAME => Some(Box::new(subsonic::SubsonicProvider::new(config))),
+        _ => None,
+    }
+}
+
+// 配置中无法识别的来源名称，创建来源时会被跳过
+pub fn unknown_providers<'a>(names: &'a [String], config: &Config) -> Vec<&'a str> {
+    names
+        .iter()
+        .filter(|name| create_provider(name, config).is_none())
+        .map(String::as_str)
+        .collect()
+}
+
+pub fn build_providers(config: &Config) -> Providers {
+    build_named_providers(&config.lyrics.providers, config)
+}
+
+pub fn build_named_providers(names: &[String], config: &Config) -> Providers {
+    Arc::new(
+        names
+            .iter()
+            .filter_map(|name| create_provider(name, config))
+            .collect(),
+    )
+}
+
+// 目录覆盖了歌词来源相关设置时为该歌曲单独创建来源，否则复用共享的来源
+pub fn providers_for_track(
+    global: &Config,
+    shared: &Providers,
+    track_config: &Config,
+) -> Providers {
+    if track_config.lyrics == global.lyrics {
+        shared.clone()
+    } else {
+        build_providers(track_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_providers() {
+        let names = vec!["lrc".to_string(), "lrc_fuzzy".into(), "embedded".into()];
+        assert_eq!(unknown_providers(&names, &Config::default()), ["lrc_fuzzy"]);
+    }
+}
@@ -0,0 +1,138 @@
+use mpd::Song;
+
+use crate::{
+    lyrics::{
+        parser::parse_lrc,
+        provider::{LyricsCandidate, LyricsProvider},
+    },
+    model::{
+        config::{Config, MpdConfig},
+        track::TrackInfo,
+    },
+    player::connection::connect,
+};
+
+pub const NAME: &str = "mpd";
+
+// MPD 通过 readcomments 返回的歌词标签名
+const LYRICS_KEYS: &[&str] = &["LYRICS", "UNSYNCEDLYRICS", "UNSYNCED LYRICS"];
+
+pub struct MpdCommentsProvider {
+    config: MpdConfig,
+}
+
+impl MpdCommentsProvider {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.mpd.clone(),
+        }
+    }
+}
+
+impl LyricsProvider for MpdCommentsProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    // 不依赖本地文件系统，直接让 MPD 读取歌曲文件的原始标签
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        let mut client = connect(&self.config)?;
+
+        let song = Song {
+            file: track.file.clone(),
+            ..Song::default()
+        };
+        let comments = client.readcomments(&song)?.collect::<Result<Vec<_>, _>>()?;
+
+        let content = comments
+            .into_iter()
+            .filter(|(key, _)| LYRICS_KEYS.iter().any(|k| key.eq_ignore_ascii_case(k)))
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let lyrics = parse_lrc(&content);
+        if lyrics.lines.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![LyricsCandidate {
+            source: NAME,
+            label: "MPD 内嵌歌词".to_string(),
+            binding: None,
+            lyrics,
+        }])
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    use super::*;
+
+    fn mock_mpd(reply: &'static str) -> (MpdConfig, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"OK MPD 0.23.5\n").unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut command = String::new();
+            reader.read_line(&mut command).unwrap();
+            stream.write_all(reply.as_bytes()).unwrap();
+            tx.send(command).unwrap();
+        });
+
+        let config = MpdConfig {
+            host: "127.0.0.1".into(),
+            port,
+            ..MpdConfig::default()
+        };
+        (config, rx)
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            file: "Artist/Album/01 Song.flac".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fetch_lyrics_from_comments() {
+        let (config, commands) =
+            mock_mpd("TITLE: Song\nLYRICS: [00:01.00]first\nLYRICS: [00:02.50]second\nOK\n");
+        let provider = MpdCommentsProvider { config };
+
+        let candidates = provider.fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        let lines = &candidates[0].lyrics.lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].text, "second");
+
+        let command = commands.recv().unwrap();
+        assert_eq!(
+            command.trim(),
+            r#"readcomments "Artist/Album/01 Song.flac""#
+        );
+    }
+
+    #[test]
+    fn test_fetch_without_lyrics() {
+        let (config, _commands) = mock_mpd("TITLE: Song\nOK\n");
+        let provider = MpdCommentsProvider { config };
+        assert!(provider.fetch(&track()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::bail;
+use serde::{Deserialize, de::DeserializeOwned};
+use ureq::Agent;
+
+use crate::{
+    lyrics::{
+        matcher::{MatchCandidate, find_best_match},
+        parser::parse_plain,
+        provider::{LyricsCandidate, LyricsProvider, http_agent},
+    },
+    model::{
+        config::{Config, SubsonicConfig},
+        lyrics::{LyricLine, Lyrics, LyricsMetadata},
+        track::TrackInfo,
+    },
+};
+
+pub const NAME: &str = "subsonic";
+
+const API_VERSION: &str = "1.16.1";
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    #[serde(rename = "subsonic-response")]
+    response: Response<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    status: String,
+    error: Option<ApiError>,
+    #[serde(flatten)]
+    body: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    code: i32,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchBody {
+    search_result3: Option<SearchResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SearchResult {
+    #[serde(default)]
+    song: Vec<SubsonicSong>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubsonicSong {
+    id: String,
+    title: Option<String>,
+    artist: Option<String>,
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LyricsListBody {
+    lyrics_list: Option<LyricsList>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LyricsList {
+    #[serde(default)]
+    structured_lyrics: Vec<StructuredLyrics>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StructuredLyrics {
+    synced: bool,
+    display_artist: Option<String>,
+    display_title: Option<String>,
+    #[serde(default)]
+    line: Vec<StructuredLine>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StructuredLine {
+    start: Option<u64>,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlainLyricsBody {
+    lyrics: Option<PlainLyrics>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlainLyrics {
+    value: Option<String>,
+}
+
+pub struct SubsonicProvider {
+    config: SubsonicConfig,
+    match_threshold: f64,
+    agent: Agent,
+}
+
+impl SubsonicProvider {
+    pub fn new(config: &Config) -> Self {
+        let subsonic = config.lyrics.subsonic.clone();
+        Self {
+            agent: http_agent(subsonic.timeout_secs),
+            config: subsonic,
+            match_threshold: config.lyrics.match_threshold,
+        }
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> anyhow::Result<T> {
+        let salt = format!(
+            "{:x}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_nanos()
+        );
+        let token = format!(
+            "{:x}",
+            md5::compute(format!("{}{salt}", self.config.password))
+        );
+
+        let url = format!("{}/rest/{endpoint}", self.config.url.trim_end_matches('/'));
+        let mut request = self
+            .agent
+            .get(&url)
+            .query("u", &self.config.username)
+            .query("t", &token)
+            .query("s", &salt)
+            .query("v", API_VERSION)
+            .query("c", "lyra")
+            .query("f", "json");
+        for (key, value) in params {
+            request = request.query(*key, *value);
+        }
+
+        let mut response = request.call()?;
+        if response.status().as_u16() != 200 {
+            bail!("Subsonic 请求失败: HTTP {}", response.status().as_u16());
+        }
+
+        let envelope: Envelope<T> = response.body_mut().read_json()?;
+        if envelope.response.status != "ok" {
+            let error = envelope.response.error;
+            bail!(
+                "Subsonic 返回错误 {}: {}",
+                error.as_ref().map_or(0, |e| e.code),
+                error.and_then(|e| e.message).unwrap_or_default()
+            );
+        }
+        Ok(envelope.response.body)
+    }
+
+    // 先按路径匹配，路径对不上时再用标签模糊匹配
+    fn find_song(&self, track: &TrackInfo) -> anyhow::Result<Option<SubsonicSong>> {
+        let query = track.title.clone().unwrap_or_else(|| {
+            Path::new(&track.file)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(&track.file)
+                .to_string()
+        });
+
+        let body: SearchBody = self.call(
+            "search3",
+            &[
+                ("query", &query),
+                ("songCount", "20"),
+                ("artistCount", "0"),
+                ("albumCount", "0"),
+            ],
+        )?;
+        let songs = body.search_result3.unwrap_or_default().song;
+
+        if let Some(index) = songs.iter().position(|song| {
+            song.path
+                .as_deref()
+                .is_some_and(|path| paths_match(path, &track.file))
+        }) {
+            return Ok(songs.into_iter().nth(index));
+        }
+
+        let candidates = songs.into_iter().map(|song| {
+            let matcher = MatchCandidate::new(
+                song.path.clone().unwrap_or_default().into(),
+                song.artist.clone(),
+                song.title.clone(),
+            );
+            (matcher, song)
+        });
+        Ok(find_best_match(track, candidates, self.match_threshold))
+    }
+
+    fn structured_lyrics(&self, song_id: &str) -> anyhow::Result<Option<Lyrics>> {
+        // 不支持 OpenSubsonic 扩展的服务器会返回错误，此时回退到 getLyrics
+        let Ok(body) = self.call::<LyricsListBody>("getLyricsBySongId", &[("id", song_id)]) else {
+            return Ok(None);
+        };
+
+        let mut list = body.lyrics_list.unwrap_or_default().structured_lyrics;
+        list.sort_by_key(|l| !l.synced);
+        Ok(list
+            .into_iter()
+            .find(|l| !l.line.is_empty())
+            .map(convert_structured))
+    }
+
+    fn plain_lyrics(&self, track: &TrackInfo) -> anyhow::Result<Option<Lyrics>> {
+        let (Some(artist), Some(title)) = (&track.artist, &track.title) else {
+            return Ok(None);
+        };
+
+        let body: PlainLyricsBody =
+            self.call("getLyrics", &[("artist", artist), ("title", title)])?;
+        Ok(body
+            .lyrics
+            .and_then(|l| l.value)
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| parse_plain(&v)))
+    }
+}
+
+// 两边的音乐根目录可能不同，只有歌手/专辑/文件三级都一致时才按后缀匹配
+const MIN_SUFFIX_COMPONENTS: usize = 3;
+
+fn path_components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect()
+}
+
+fn paths_match(server_path: &str, mpd_path: &str) -> bool {
+    let server = path_components(server_path);
+    let mpd = path_components(mpd_path);
+    if server == mpd {
+        return true;
+    }
+    let (longer, shorter) = if server.len() > mpd.len() {
+        (&server, &mpd)
+    } else {
+        (&mpd, &server)
+    };
+    shorter.len() >= MIN_SUFFIX_COMPONENTS && longer.ends_with(shorter)
+}
+
+fn convert_structured(structured: StructuredLyrics) -> Lyrics {
+    let lines = structured
+        .line
+        .into_iter()
+        .map(|line| LyricLine {
+            timestamp: Duration::from_millis(line.start.unwrap_or_default()),
+            text: line.value.trim().to_string(),
+            ..LyricLine::default()
+        })
+        .collect();
+
+    Lyrics {
+        metadata: LyricsMetadata {
+            artist: structured.display_artist,
+            title: structured.display_title,
+            ..LyricsMetadata::default()
+        },
+        lines,
+        synced: structured.synced,
+        ..Lyrics::default()
+    }
+}
+
+impl LyricsProvider for SubsonicProvider {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn fetch(&self, track: &TrackInfo) -> anyhow::Result<Vec<LyricsCandidate>> {
+        if self.config.url.is_empty() {
+            bail!("未配置 Subsonic 服务器地址");
+        }
+
+        let lyrics = match self.find_song(track)? {
+            Some(song) => match self.structured_lyrics(&song.id)? {
+                Some(lyrics) => Some(lyrics),
+                None => self.plain_lyrics(track)?,
+            },
+            None => self.plain_lyrics(track)?,
+        };
+
+        Ok(lyrics
+            .map(|lyrics| LyricsCandidate {
+                source: NAME,
+                label: self.config.url.clone(),
+                binding: None,
+                lyrics,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::provider::mock_http;
+
+    fn provider(url: String) -> SubsonicProvider {
+        let mut config = Config::default();
+        config.lyrics.subsonic = SubsonicConfig {
+            url,
+            username: "user".into(),
+            password: "secret".into(),
+            ..SubsonicConfig::default()
+        };
+        SubsonicProvider::new(&config)
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            file: "Artist/Album/01 Song.flac".into(),
+            artist: Some("Artist".into()),
+            title: Some("Song".into()),
+            ..Default::default()
+        }
+    }
+
+    const SEARCH: &str = r#"{"subsonic-response":{"status":"ok","searchResult3":{"song":[
+        {"id":"1","title":"Song","artist":"Someone Else","path":"Other/Song.flac"},
+        {"id":"2","title":"Song","artist":"Artist","path":"Artist/Album/01 Song.flac"}]}}}"#;
+
+    #[test]
+    fn test_fetch_structured_lyrics() {
+        let lyrics = r#"{"subsonic-response":{"status":"ok","lyricsList":{"structuredLyrics":[
+            {"synced":false,"line":[{"value":"plain"}]},
+            {"synced":true,"line":[{"start":1000,"value":"first"},{"start":2500,"value":"second"}]}]}}}"#;
+        let (url, requests) = mock_http::serve(vec![
+            ("/rest/search3", "200 OK", SEARCH.to_string()),
+            ("/rest/getLyricsBySongId", "200 OK", lyrics.to_string()),
+        ]);
+
+        let candidates = provider(url).fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        let lyrics = &candidates[0].lyrics;
+        assert!(lyrics.synced);
+        assert_eq!(lyrics.lines[1].timestamp, Duration::from_millis(2500));
+        assert_eq!(lyrics.lines[1].text, "second");
+
+        let search = requests.recv().unwrap();
+        assert!(search.contains("u=user"));
+        assert!(search.contains("f=json"));
+        assert!(!search.contains("secret"));
+        let get_lyrics = requests.recv().unwrap();
+        assert!(get_lyrics.contains("id=2"));
+    }
+
+    #[test]
+    fn test_fallback_to_get_lyrics() {
+        let unsupported = r#"{"subsonic-response":{"status":"failed","error":{"code":70,"message":"not found"}}}"#;
+        let plain = r#"{"subsonic-response":{"status":"ok","lyrics":{"artist":"Artist","title":"Song","value":"first\nsecond"}}}"#;
+        let (url, _requests) = mock_http::serve(vec![
+            ("/rest/search3", "200 OK", SEARCH.to_string()),
+            ("/rest/getLyricsBySongId", "200 OK", unsupported.to_string()),
+            ("/rest/getLyrics", "200 OK", plain.to_string()),
+        ]);
+
+        let candidates = provider(url).fetch(&track()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert!(!candidates[0].lyrics.synced);
+        assert_eq!(candidates[0].lyrics.lines.len(), 2);
+    }
+
+    #[test]
+    fn test_paths_match() {
+        assert!(paths_match(
+            "Artist/Album/01 Song.flac",
+            "Artist/Album/01 Song.flac"
+        ));
+        assert!(paths_match(
+            "/music/Artist/Album/01 Song.flac",
+            "Artist/Album/01 Song.flac"
+        ));
+        assert!(paths_match(
+            "Artist\\Album\\01 Song.flac",
+            "./Artist/Album/01 Song.flac"
+        ));
+        assert!(!paths_match(
+            "Other/01 Song.flac",
+            "Artist/Album/01 Song.flac"
+        ));
+        // 只有文件名或专辑/文件名相同不能说明是同一首歌
+        assert!(!paths_match("Other Artist/Song.flac", "Song.flac"));
+        assert!(!paths_match(
+            "Album/01 Song.flac",
+            "Other Artist/Album/01 Song.flac"
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use crate::{
+    lyrics::provider::LyricsCandidate,
+    model::{lyrics::Lyrics, track::TrackInfo},
+};
+
+const SYNCED_SCORE: f64 = 0.5;
+const WORD_TIMED_SCORE: f64 = 0.2;
+const DURATION_SCORE: f64 = 0.3;
+const INVALID_PENALTY: f64 = 0.5;
+
+// 时长误差在容差内视为吻合，超出部分在 DURATION_FALLOFF_SECS 秒内逐渐扣完
+const DURATION_TOLERANCE: Duration = Duration::from_secs(3);
+const DURATION_FALLOFF_SECS: f64 = 20.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CandidateScore {
+    pub timing: f64,
+    pub duration: f64,
+    pub penalty: f64,
+    // [length:] 标签或最后一行的时间，调试视图中与歌曲时长对照
+    pub lyrics_end: Option<Duration>,
+}
+
+impl CandidateScore {
+    pub fn total(&self) -> f64 {
+        self.timing + self.duration - self.penalty
+    }
+
+    // 时长吻合且几乎没有无法解析行的同步歌词，开启 local_first 时本地找到后不再查询在线来源
+    pub fn is_good(&self) -> bool {
+        self.timing >= SYNCED_SCORE
+            && self.duration >= DURATION_SCORE / 2.0
+            && self.penalty < INVALID_PENALTY / 4.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoredCandidate {
+    pub candidate: LyricsCandidate,
+    pub score: CandidateScore,
+}
+
+fn duration_score(track: &TrackInfo, lyrics: &Lyrics) -> (f64, Option<Duration>) {
+    if !lyrics.synced {
+        return (DURATION_SCORE / 2.0, None);
+    }
+
+    let last_line = lyrics.lines.last().map(|l| l.timestamp);
+    let lyrics_end = lyrics.metadata.length.or(last_line);
+    let (Some(song_length), Some(end)) = (track.duration, lyrics_end) else {
+        return (DURATION_SCORE / 2.0, lyrics_end);
+    };
+
+    let diff = match lyrics.metadata.length {
+        Some(length) => length.abs_diff(song_length),
+        // 最后一行通常早于歌曲结束，只有超出歌曲时长才说明版本不对
+        None => end.saturating_sub(song_length),
+    };
+    let excess = diff.saturating_sub(DURATION_TOLERANCE).as_secs_f64();
+    let score = DURATION_SCORE * (1.0 - excess / DURATION_FALLOFF_SECS).max(0.0);
+    (score, lyrics_end)
+}
+
+pub fn score_lyrics(track: &TrackInfo, lyrics: &Lyrics) -> CandidateScore {
+    let mut timing = 0.0;
+    if lyrics.synced {
+        timing += SYNCED_SCORE;
+    }
+    if lyrics.word_timed {
+        timing += WORD_TIMED_SCORE;
+    }
+
+    let (duration, lyrics_end) = duration_score(track, lyrics);
+
+    let total_lines = lyrics.lines.len() + lyrics.invalid_lines;
+    let penalty = if total_lines == 0 {
+        0.0
+    } else {
+        INVALID_PENALTY * lyrics.invalid_lines as f64 / total_lines as f64
+    };
+
+    CandidateScore {
+        timing,
+        duration,
+        penalty,
+        lyrics_end,
+    }
+}
+
+// 按得分从高到低排序，得分相同时保持来源的配置顺序
+pub fn rank_candidates(
+    track: &TrackInfo,
+    candidates: impl IntoIterator<Item = LyricsCandidate>,
+) -> Vec<ScoredCandidate> {
+    let mut scored: Vec<ScoredCandidate> = candidates
+        .into_iter()
+        .map(|candidate| ScoredCandidate {
+            score: score_lyrics(track, &candidate.lyrics),
+            candidate,
+        })
+        .collect();
+    scored.sort_by(|a, b| b.score.total().total_cmp(&a.score.total()));
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::parser::parse_lrc;
+
+    fn candidate(label: &str, content: &str) -> LyricsCandidate {
+        LyricsCandidate {
+            source: "lrc",
+            label: label.into(),
+            binding: None,
+            lyrics: parse_lrc(content),
+        }
+    }
+
+    fn track() -> TrackInfo {
+        TrackInfo {
+            duration: Some(Duration::from_secs(200)),
+            ..Default::default()
+        }
+    }
+
+    fn ranked_labels(candidates: Vec<LyricsCandidate>) -> Vec<String> {
+        rank_candidates(&track(), candidates)
+            .into_iter()
+            .map(|s| s.candidate.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_prefer_synced_and_word_timed() {
+        let labels = ranked_labels(vec![
+            candidate("plain", "first\nsecond"),
+            candidate("line", "[00:01.00]first\n[00:02.00]second"),
+            candidate(
+                "word",
+                "[00:01.00]<00:01.00>fi<00:01.50>rst\n[00:02.00]second",
+            ),
+        ]);
+        assert_eq!(labels, ["word", "line", "plain"]);
+    }
+
+    #[test]
+    fn test_duration_mismatch() {
+        let labels = ranked_labels(vec![
+            candidate("long", "[length:04:30]\n[00:01.00]first"),
+            candidate("overrun", "[00:01.00]first\n[04:00.00]last"),
+            candidate("matching", "[length:03:21]\n[00:01.00]first"),
+        ]);
+        assert_eq!(labels, ["matching", "long", "overrun"]);
+
+        let score = score_lyrics(&track(), &parse_lrc("[00:01.00]first\n[03:10.00]last"));
+        assert_eq!(score.duration, DURATION_SCORE);
+        assert_eq!(score.lyrics_end, Some(Duration::from_secs(190)));
+    }
+
+    #[test]
+    fn test_invalid_lines_penalty() {
+        let messy = candidate(
+            "messy",
+            "[00:01.00]first\nbroken\nalso broken\n[00:02.00]second",
+        );
+        assert_eq!(messy.lyrics.invalid_lines, 2);
+
+        let labels = ranked_labels(vec![
+            messy,
+            candidate("clean", "[by:someone]\n[00:01.00]first\n[00:02.00]second"),
+        ]);
+        assert_eq!(labels, ["clean", "messy"]);
+    }
+
+    #[test]
+    fn test_good_candidate() {
+        let good = |content| score_lyrics(&track(), &parse_lrc(content)).is_good();
+        assert!(good("[length:03:20]\n[00:01.00]first"));
+        assert!(good("[00:01.00]first"));
+        assert!(!good("first\nsecond"));
+        assert!(!good("[length:04:30]\n[00:01.00]first"));
+        assert!(!good("[00:01.00]first\nbroken\n[00:02.00]second"));
+    }
+
+    #[test]
+    fn test_keep_provider_order_on_tie() {
+        let labels = ranked_labels(vec![
+            candidate("first", "[00:01.00]a"),
+            candidate("second", "[00:01.00]b"),
+        ]);
+        assert_eq!(labels, ["first", "second"]);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use encoding_rs::Encoding;
+
+use crate::{
+    lyrics::{
+        mapping::LyricsBinding,
+        parser::{find_lrc_file, from_lrc},
+        provider::LyricsCandidate,
+    },
+    model::{config::Config, lyrics::Lyrics, track::TrackInfo},
+};
+
+// song.zh.lrc 这类文件只作为翻译层，不参与主歌词的查找
+pub fn is_translation_file(path: &Path, suffixes: &[String]) -> bool {
+    path.file_stem()
+        .and_then(|stem| Path::new(stem).extension())
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| suffixes.iter().any(|s| s.eq_ignore_ascii_case(ext)))
+}
+
+// 优先找主歌词文件旁的同名翻译，其次在歌词目录中按歌曲文件名查找
+fn companion_paths(
+    config: &Config,
+    track: &TrackInfo,
+    candidate: &LyricsCandidate,
+) -> Vec<(String, PathBuf)> {
+    let main_base = match &candidate.binding {
+        Some(LyricsBinding::File { path }) => Some(path.with_extension("")),
+        _ => None,
+    };
+    let track_stem = Path::new(&track.file)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&track.file);
+
+    config
+        .lyrics
+        .translation_suffixes
+        .iter()
+        .filter_map(|suffix| {
+            let sibling = main_base.as_ref().map(|base| {
+                let mut name = base.clone().into_os_string();
+                name.push(format!(".{suffix}.lrc"));
+                PathBuf::from(name)
+            });
+            let path = sibling.filter(|p| p.is_file()).or_else(|| {
+                find_lrc_file(&config.paths.lyrics_dir, &format!("{track_stem}.{suffix}"))
+            })?;
+            Some((suffix.clone(), path))
+        })
+        .collect()
+}
+
+// 同步歌词按时间戳就近合并，纯文本歌词按行号合并；返回是否合并了任何一行
+pub fn merge_layer(
+    lyrics: &mut Lyrics,
+    layer: &str,
+    translation: &Lyrics,
+    tolerance: Duration,
+) -> bool {
+    let mut merged = false;
+
+    match (lyrics.synced, translation.synced) {
+        (true, true) => {
+            for line in translation.lines.iter().filter(|l| !l.text.is_empty()) {
+                let nearest = lyrics
+                    .lines
+                    .iter_mut()
+                    .filter(|main| main.timestamp.abs_diff(line.timestamp) <= tolerance)
+                    .min_by_key(|main| main.timestamp.abs_diff(line.timestamp));
+                if let Some(main) = nearest
+                    && !main.translations.contains_key(layer)
+                {
+                    main.translations
+                        .insert(layer.to_string(), line.text.clone());
+                    merged = true;
+                }
+            }
+        }
+        (false, false) => {
+            for (main, line) in lyrics.lines.iter_mut().zip(&translation.lines) {
+                if !line.text.is_empty() {
+                    main.translations
+                        .insert(layer.to_string(), line.text.clone());
+                    merged = true;
+                }
+            }
+        }
+        // 同步与纯文本之间无法对齐
+        _ => {}
+    }
+
+    if merged {
+        lyrics.layers.push(layer.to_string());
+    }
+    merged
+}
+
+pub fn attach_translations(config: &Config, track: &TrackInfo, candidate: &mut LyricsCandidate) {
+    let encoding = Encoding::for_label(config.lyrics.encoding.as_bytes());
+    let tolerance = Duration::from_millis(config.lyrics.translation_tolerance_ms);
+
+    for (layer, path) in companion_paths(config, track, candidate) {
+        if let Ok(translation) = from_lrc(&path, encoding) {
+            merge_layer(&mut candidate.lyrics, &layer, &translation, tolerance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::lyrics::parser::parse_lrc;
+
+    #[test]
+    fn test_is_translation_file() {
+        let suffixes = vec!["zh".to_string(), "romaji".to_string()];
+        assert!(is_translation_file(Path::new("a/song.zh.lrc"), &suffixes));
+        assert!(is_translation_file(Path::new("song.ROMAJI.lrc"), &suffixes));
+        assert!(!is_translation_file(Path::new("song.lrc"), &suffixes));
+        assert!(!is_translation_file(Path::new("Mr. Song.lrc"), &suffixes));
+    }
+
+    #[test]
+    fn test_merge_with_drift() {
+        let mut lyrics = parse_lrc("[00:01.00]one\n[00:05.00]two\n[00:09.00]three");
+        let translation = parse_lrc("[00:01.30]一\n[00:04.80]二\n[00:12.00]太远");
+        assert!(merge_layer(
+            &mut lyrics,
+            "zh",
+            &translation,
+            Duration::from_millis(500)
+        ));
+
+        assert_eq!(lyrics.layers, ["zh"]);
+        assert_eq!(lyrics.lines[0].translations["zh"], "一");
+        assert_eq!(lyrics.lines[1].translations["zh"], "二");
+        assert!(lyrics.lines[2].translations.is_empty());
+
+        let plain = parse_lrc("one\ntwo");
+        assert!(!merge_layer(
+            &mut lyrics,
+            "trans",
+            &plain,
+            Duration::from_millis(500)
+        ));
+        assert_eq!(lyrics.layers, ["zh"]);
+    }
+
+    #[test]
+    fn test_attach_companion_files() {
+        let dir = tempdir().unwrap();
+        let main = dir.path().join("song.lrc");
+        fs::write(&main, "[00:01.00]one\n[00:02.00]two").unwrap();
+        fs::write(dir.path().join("song.romaji.lrc"), "[00:01.00]ichi").unwrap();
+        fs::write(dir.path().join("song.zh.lrc"), "[00:01.10]一\n[00:02.00]二").unwrap();
+
+        let mut config = Config::default();
+        config.paths.lyrics_dir = dir.path().to_path_buf();
+        let track = TrackInfo {
+            file: "Artist/song.flac".into(),
+            ..Default::default()
+        };
+        let mut candidate = LyricsCandidate {
+            source: "lrc",
+            label: "song.lrc".into(),
+            binding: Some(LyricsBinding::File { path: main.clone() }),
+            lyrics: from_lrc(&main, None).unwrap(),
+        };
+
+        attach_translations(&config, &track, &mut candidate);
+        assert_eq!(candidate.lyrics.layers, ["zh", "romaji"]);
+        assert_eq!(candidate.lyrics.lines[0].translations.len(), 2);
+        assert_eq!(candidate.lyrics.lines[1].translations["zh"], "二");
+    }
+}
@@ -1,54 +1,45 @@
 use std::io;
 
-use anyhow::Context;
+use clap::Parser;
 use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use mpd::Client;
 use ratatui::{Terminal, prelude::CrosstermBackend};
 
-use crate::{config::loader::load_config, lyrics::parser::find_lyrics_for_track, ui::app::App};
+use crate::{
+    cli::{Cli, Command},
+    config::loader::load_config,
+    lyrics::provider::build_providers,
+    ui::app::App,
+};
 
+mod cli;
 mod config;
 mod lyrics;
 mod model;
+mod player;
 mod ui;
 fn main() -> anyhow::Result<()> {
-    let config = load_config();
-
-    let mut mpd_client =
-        Client::connect(config.mpd.get_address()).with_context(|| "连接MPD服务失败")?;
-
-    let mpd_password = &config.mpd.password;
-    if !mpd_password.is_empty() {
-        mpd_client
-            .login(mpd_password)
-            .with_context(|| "MPD认证失败")?;
+    let cli = Cli::parse();
+    let mut config = load_config();
+    // 保留全部服务器配置，供界面中切换
+    let servers = config.mpd.clone();
+    if let Some(name) = &cli.profile {
+        config.mpd = servers
+            .profile(name)
+            .ok_or_else(|| anyhow::anyhow!("未找到 MPD 服务器配置: {name}"))?;
     }
 
-    let status = mpd_client.status().with_context(|| "获取MPD状态失败")?;
-    let song = mpd_client
-        .currentsong()
-        .with_context(|| "获取当前播放歌曲失败")?;
-
-    let (current_song_file_name, current_song_title) = match song {
-        Some(s) => (Some(s.file), s.title),
-        None => (None, None),
-    };
-    let current_lyrics = current_song_file_name
-        .as_ref()
-        .and_then(|s| find_lyrics_for_track(&config.paths, s).ok())
-        .flatten();
+    if let Some(command) = cli.command {
+        return match command {
+            Command::Fetch(args) => cli::fetch::run(config, args),
+            Command::Missing(args) => cli::missing::run(args),
+        };
+    }
 
-    let app = App::new(
-        config,
-        mpd_client,
-        status.state,
-        current_lyrics,
-        current_song_file_name,
-        current_song_title,
-    );
+    let providers = build_providers(&config);
+    let app = App::new(config, providers, cli.profile);
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -57,12 +48,22 @@ fn main() -> anyhow::Result<()> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let mut controller = crate::ui::controller::Controller::new(app);
-    loop {
-        if controller.app.should_quit {
-            break;
-        }
+    let mut controller = crate::ui::controller::Controller::new(app, servers);
+    let result = run_loop(&mut terminal, &mut controller);
+
+    // 无论主循环是否出错都恢复终端
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
 
+fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    controller: &mut crate::ui::controller::Controller,
+) -> anyhow::Result<()> {
+    while !controller.app.should_quit {
         terminal.draw(|frame| {
             crate::ui::renderer::render(&controller.app, frame);
         })?;
@@ -71,10 +72,5 @@ fn main() -> anyhow::Result<()> {
 
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
-
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
     Ok(())
 }
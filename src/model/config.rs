@@ -1,18 +1,138 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
+    collections::BTreeMap,
     env::var,
+    fmt,
     path::{Path, PathBuf},
+    time::Duration,
 };
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct MpdConfig {
     pub host: String,
     pub port: u16,
     pub password: String,
+    // 从文件(如 systemd credential)或命令(如 pass show mpd)读取密码，优先于 password
+    pub password_file: String,
+    pub password_command: String,
+    // 为空时使用MPD的默认分区
+    pub partition: String,
+    // [mpd.profiles.<name>] 中的命名服务器，未写出的字段沿用 [mpd]
+    pub profiles: BTreeMap<String, MpdProfile>,
 }
+
+#[derive(Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MpdProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partition: Option<String>,
+}
+
+// Debug 输出中隐藏密码，避免出现在日志或错误信息里
+fn redact(secret: &str) -> &str {
+    if secret.is_empty() { "" } else { "<redacted>" }
+}
+
+impl fmt::Debug for MpdConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MpdConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("password", &redact(&self.password))
+            .field("password_file", &self.password_file)
+            .field("password_command", &self.password_command)
+            .field("partition", &self.partition)
+            .field("profiles", &self.profiles)
+            .finish()
+    }
+}
+
+impl fmt::Debug for MpdProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MpdProfile")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("password", &self.password.as_deref().map(redact))
+            .field("password_file", &self.password_file)
+            .field("password_command", &self.password_command)
+            .field("partition", &self.partition)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MpdAddress {
+    Tcp(String),
+    Socket(PathBuf),
+}
+
 impl MpdConfig {
+    // host 为绝对路径(或 ~ 开头)时通过 Unix 套接字连接，@ 开头为 Linux 抽象套接字
+    pub fn address(&self) -> MpdAddress {
+        if self.host.starts_with(['/', '~', '@']) {
+            MpdAddress::Socket(expand_tilde(&self.host))
+        } else if self.host.contains(':') {
+            MpdAddress::Tcp(format!("[{}]:{}", self.host, self.port))
+        } else {
+            MpdAddress::Tcp(format!("{}:{}", self.host, self.port))
+        }
+    }
+
     pub fn get_address(&self) -> String {
-        format!("{}:{}", self.host, self.port)
+        match self.address() {
+            MpdAddress::Tcp(address) => address,
+            MpdAddress::Socket(path) => path.display().to_string(),
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Option<MpdConfig> {
+        let profile = self.profiles.get(name)?;
+        let pick = |value: &Option<String>, base: &String| value.clone().unwrap_or(base.clone());
+        // 服务器设置了任一密码来源时不再沿用 [mpd] 的密码
+        let own_password = profile.password.is_some()
+            || profile.password_file.is_some()
+            || profile.password_command.is_some();
+        let base = if own_password {
+            &MpdConfig::default()
+        } else {
+            self
+        };
+        Some(MpdConfig {
+            host: pick(&profile.host, &self.host),
+            port: profile.port.unwrap_or(self.port),
+            password: pick(&profile.password, &base.password),
+            password_file: pick(&profile.password_file, &base.password_file),
+            password_command: pick(&profile.password_command, &base.password_command),
+            partition: pick(&profile.partition, &self.partition),
+            profiles: self.profiles.clone(),
+        })
+    }
+
+    // 与其他MPD客户端一致，MPD_HOST 支持 password@host 形式，优先于配置文件
+    pub fn apply_env(&mut self, host: Option<&str>, port: Option<&str>) {
+        if let Some(host) = host.filter(|h| !h.is_empty()) {
+            match host.split_once('@') {
+                Some((password, host)) if !password.is_empty() => {
+                    self.password = password.to_string();
+                    self.password_file.clear();
+                    self.password_command.clear();
+                    self.host = host.to_string();
+                }
+                _ => self.host = host.to_string(),
+            }
+        }
+        if let Some(port) = port.and_then(|p| p.parse().ok()) {
+            self.port = port;
+        }
     }
 }
 
@@ -24,34 +144,186 @@ struct RawPathConfig {
     lyrics_dir: Option<PathBuf>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(from = "RawPathConfig")]
 pub struct PathConfig {
     pub music_dir: PathBuf,
     pub lyrics_dir: PathBuf,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct LyricStyleConfig {
     pub color: String,
     pub bold: bool,
+    // 翻译显示方式: "all"、"none" 或某个翻译后缀
+    pub translation: String,
     pub current: LyricStateOverride,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default)]
 pub struct LyricStateOverride {
     pub color: String,
     pub bold: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LyricsConfig {
+    pub providers: Vec<String>,
+    pub match_threshold: f64,
+    // 强制使用的 LRC 文件编码，如 "gbk"，留空按 UTF-8 读取
+    pub encoding: String,
+    // 翻译文件后缀，如 song.zh.lrc 中的 "zh"
+    pub translation_suffixes: Vec<String>,
+    // 翻译行与原文行时间戳允许的误差
+    pub translation_tolerance_ms: u64,
+    // 本地来源已有合适的同步歌词时不再查询在线来源
+    pub local_first: bool,
+    pub command: CommandProviderConfig,
+    pub lrclib: LrclibConfig,
+    pub subsonic: SubsonicConfig,
+    pub beets: BeetsConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CommandProviderConfig {
+    pub program: String,
+    pub args: Vec<String>,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(default)]
 pub struct Config {
     pub mpd: MpdConfig,
     pub paths: PathConfig,
+    pub lyrics: LyricsConfig,
+    pub sync: SyncConfig,
+    pub fetch: FetchConfig,
+    pub ab_loop: AbLoopConfig,
     pub lyric_style: LyricStyleConfig,
+    // MPD 输出名称 -> 该输出相对 elapsed 的延迟(毫秒)
+    pub output_latency: BTreeMap<String, u64>,
+}
+
+impl Config {
+    // 同时启用多个输出时按延迟最大的补偿
+    pub fn output_latency(&self, enabled_outputs: &[String]) -> Duration {
+        let latency_ms = enabled_outputs
+            .iter()
+            .filter_map(|name| self.output_latency.get(name))
+            .max()
+            .copied()
+            .unwrap_or_default();
+        Duration::from_millis(latency_ms)
+    }
+
+    pub fn has_plaintext_password(&self) -> bool {
+        !self.mpd.password.is_empty()
+            || self
+                .mpd
+                .profiles
+                .values()
+                .any(|p| p.password.as_ref().is_some_and(|p| !p.is_empty()))
+            || !self.lyrics.subsonic.password.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FetchConfig {
+    // lyra-rs fetch 为缺少歌词的歌曲使用的来源
+    pub providers: Vec<String>,
+    pub jobs: usize,
+    // 相对于 lyrics_dir 的文件名模板
+    pub template: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct AbLoopConfig {
+    // 循环次数，0 表示一直循环直到取消
+    pub repeat: u32,
+    // 每遍之间暂停的毫秒数
+    pub gap_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(default)]
+pub struct SyncConfig {
+    // 正值让歌词提前显示，负值让歌词延后显示
+    pub offset_ms: i64,
+    // 按 +/- 调整的单曲偏移保存的位置
+    pub save_to: OffsetStore,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OffsetStore {
+    // 写入 LRC 文件的 [offset:] 标签
+    Lrc,
+    // 以歌曲路径为键保存在 MPD sticker 中
+    Sticker,
+    // 本地的 offsets.toml
+    #[default]
+    Database,
+}
+
+impl SyncConfig {
+    pub fn apply(&self, elapsed: Duration) -> Duration {
+        let offset = Duration::from_millis(self.offset_ms.unsigned_abs());
+        if self.offset_ms >= 0 {
+            elapsed + offset
+        } else {
+            elapsed.saturating_sub(offset)
+        }
+    }
+
+    // apply 的逆运算：让某个歌词时间点显示时对应的播放进度
+    pub fn playback_time(&self, lyrics_time: Duration) -> Duration {
+        let offset = Duration::from_millis(self.offset_ms.unsigned_abs());
+        if self.offset_ms >= 0 {
+            lyrics_time.saturating_sub(offset)
+        } else {
+            lyrics_time + offset
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LrclibSaveTarget {
+    Cache,
+    Track,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LrclibConfig {
+    pub base_url: String,
+    pub timeout_secs: u64,
+    pub save_to: LrclibSaveTarget,
+    #[serde(deserialize_with = "deserialize_path")]
+    pub cache_dir: PathBuf,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SubsonicConfig {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BeetsConfig {
+    #[serde(deserialize_with = "deserialize_path")]
+    pub library: PathBuf,
 }
 
 impl Default for MpdConfig {
@@ -60,6 +332,10 @@ impl Default for MpdConfig {
             host: "127.0.0.1".into(),
             port: 6600,
             password: "".into(),
+            password_file: "".into(),
+            password_command: "".into(),
+            partition: "".into(),
+            profiles: BTreeMap::new(),
         }
     }
 }
@@ -84,11 +360,83 @@ impl Default for PathConfig {
     }
 }
 
+impl Default for LyricsConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec!["lrc".into(), "embedded".into(), "lrc-fuzzy".into()],
+            match_threshold: 0.8,
+            encoding: "".into(),
+            translation_suffixes: vec!["zh".into(), "trans".into(), "romaji".into()],
+            translation_tolerance_ms: 500,
+            local_first: false,
+            command: CommandProviderConfig::default(),
+            lrclib: LrclibConfig::default(),
+            subsonic: SubsonicConfig::default(),
+            beets: BeetsConfig::default(),
+        }
+    }
+}
+
+impl Default for CommandProviderConfig {
+    fn default() -> Self {
+        Self {
+            program: "".into(),
+            args: Vec::new(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for LrclibConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://lrclib.net".into(),
+            timeout_secs: 10,
+            save_to: LrclibSaveTarget::Cache,
+            cache_dir: dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from("~/.cache"))
+                .join("lyra/lrclib"),
+        }
+    }
+}
+
+impl Default for SubsonicConfig {
+    fn default() -> Self {
+        Self {
+            url: "".into(),
+            username: "".into(),
+            password: "".into(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl Default for BeetsConfig {
+    fn default() -> Self {
+        Self {
+            library: dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("~"))
+                .join(".config/beets/library.db"),
+        }
+    }
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec!["lrclib".into()],
+            jobs: 4,
+            template: "{dir}/{stem}.lrc".into(),
+        }
+    }
+}
+
 impl Default for LyricStyleConfig {
     fn default() -> Self {
         Self {
             color: "#AAAAAA".into(),
             bold: false,
+            translation: "all".into(),
             current: LyricStateOverride::default(),
         }
     }
@@ -103,7 +451,22 @@ impl Default for LyricStateOverride {
     }
 }
 
-fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
+impl fmt::Debug for SubsonicConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubsonicConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &redact(&self.password))
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+fn deserialize_path<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+    PathBuf::deserialize(deserializer).map(expand_tilde)
+}
+
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> PathBuf {
     let path = path.as_ref();
     if path.starts_with("~") {
         if let Ok(home) = var("HOME") {
@@ -133,6 +496,97 @@ mod tests {
         assert_eq!(cfg.password, "");
     }
 
+    #[test]
+    fn test_mpd_profiles() {
+        let config: Config = toml::from_str(
+            r#"
+[mpd]
+host = "desktop.local"
+password = "secret"
+
+[mpd.profiles.living-room]
+host = "192.168.1.20"
+partition = "speakers"
+
+[mpd.profiles.work]
+host = "work.example.com"
+port = 6601
+password = ""
+"#,
+        )
+        .unwrap();
+        let mpd = &config.mpd;
+        assert_eq!(mpd.host, "desktop.local");
+        assert_eq!(mpd.profiles.len(), 2);
+
+        let living_room = mpd.profile("living-room").unwrap();
+        assert_eq!(living_room.get_address(), "192.168.1.20:6600");
+        assert_eq!(living_room.password, "secret");
+        assert_eq!(living_room.partition, "speakers");
+
+        let work = mpd.profile("work").unwrap();
+        assert_eq!(work.get_address(), "work.example.com:6601");
+        assert_eq!(work.password, "");
+        assert!(mpd.profile("missing").is_none());
+
+        // 按目录覆盖配置时会序列化整个配置
+        let value = toml::Value::try_from(&config).unwrap();
+        assert_eq!(value.try_into::<Config>().unwrap().mpd, config.mpd);
+    }
+
+    #[test]
+    fn test_mpd_unknown_key() {
+        // 未知的设置只被忽略，不会让整个配置文件解析失败
+        let config: Config = toml::from_str(
+            "[mpd]\nhost = \"desktop.local\"\ntimeout = 5\n[mpd.profiles.work]\nport = 6601",
+        )
+        .unwrap();
+        assert_eq!(config.mpd.host, "desktop.local");
+        assert_eq!(config.mpd.profiles.len(), 1);
+        assert_eq!(config.mpd.profile("work").unwrap().port, 6601);
+    }
+
+    #[test]
+    fn test_password_redacted() {
+        let mut config: Config = toml::from_str(
+            "[mpd]\npassword = \"hunter2\"\n[mpd.profiles.work]\npassword_command = \"pass show mpd\"\n[lyrics.subsonic]\npassword = \"hunter3\"",
+        )
+        .unwrap();
+        assert!(config.has_plaintext_password());
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("hunter2") && !debug.contains("hunter3"));
+        assert!(debug.contains("<redacted>"));
+
+        // 服务器自己的密码来源不与 [mpd] 的密码混用
+        let work = config.mpd.profile("work").unwrap();
+        assert_eq!(work.password, "");
+        assert_eq!(work.password_command, "pass show mpd");
+
+        config.mpd.password.clear();
+        config.lyrics.subsonic.password.clear();
+        assert!(!config.has_plaintext_password());
+    }
+
+    #[test]
+    fn test_mpd_env() {
+        let mut cfg = MpdConfig::default();
+        cfg.apply_env(Some("secret@music.local"), Some("6601"));
+        assert_eq!(cfg.password, "secret");
+        assert_eq!(cfg.get_address(), "music.local:6601");
+
+        let mut cfg = MpdConfig::default();
+        cfg.apply_env(Some("/run/mpd/socket"), Some("invalid"));
+        assert_eq!(cfg.address(), MpdAddress::Socket("/run/mpd/socket".into()));
+        assert_eq!(cfg.port, 6600);
+
+        let mut cfg = MpdConfig::default();
+        cfg.apply_env(Some("::1"), None);
+        assert_eq!(cfg.address(), MpdAddress::Tcp("[::1]:6600".into()));
+        cfg.apply_env(Some("@mpd"), None);
+        assert_eq!(cfg.address(), MpdAddress::Socket("@mpd".into()));
+        assert_eq!(cfg.password, "");
+    }
+
     #[test]
     fn test_path_config_default() {
         let cfg = PathConfig::default();
@@ -154,14 +608,60 @@ mod tests {
     #[test]
     fn test_lyric_style_default() {
         let cfg = LyricStyleConfig::default();
-        assert_eq!(cfg.color, "#FFFFFF");
+        assert_eq!(cfg.color, "#AAAAAA");
         assert!(!cfg.bold);
 
         let current = cfg.current;
-        assert_eq!(current.color, "#FFFFFF");
+        assert_eq!(current.color, "#00FF7F");
         assert!(current.bold);
     }
 
+    #[test]
+    fn test_lyrics_config_default() {
+        let cfg = LyricsConfig::default();
+        assert_eq!(cfg.providers, vec!["lrc", "embedded", "lrc-fuzzy"]);
+        assert_eq!(cfg.match_threshold, 0.8);
+    }
+
+    #[test]
+    fn test_sync_offset() {
+        let elapsed = Duration::from_millis(1000);
+        let sync = |offset_ms| SyncConfig {
+            offset_ms,
+            ..Default::default()
+        };
+        assert_eq!(sync(0).apply(elapsed), elapsed);
+        assert_eq!(sync(250).apply(elapsed), Duration::from_millis(1250));
+        assert_eq!(sync(-250).apply(elapsed), Duration::from_millis(750));
+        assert_eq!(sync(-2000).apply(elapsed), Duration::ZERO);
+        assert_eq!(sync(250).playback_time(elapsed), Duration::from_millis(750));
+        assert_eq!(
+            sync(-250).playback_time(elapsed),
+            Duration::from_millis(1250)
+        );
+    }
+
+    #[test]
+    fn test_output_latency() {
+        let mut config = Config::default();
+        config.output_latency.insert("Bluetooth".into(), 250);
+        config.output_latency.insert("Snapcast".into(), 800);
+        let outputs = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(config.output_latency(&[]), Duration::ZERO);
+        assert_eq!(
+            config.output_latency(&outputs(&["ALSA", "Bluetooth"])),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            config.output_latency(&outputs(&["Bluetooth", "Snapcast"])),
+            Duration::from_millis(800)
+        );
+
+        let parsed: Config = toml::from_str("[output_latency]\n\"My Speaker\" = 300").unwrap();
+        assert_eq!(parsed.output_latency.get("My Speaker"), Some(&300));
+    }
+
     #[test]
     fn test_config_default() {
         let cfg = Config::default();
@@ -1,12 +1,120 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct Lyrics {
+    pub metadata: LyricsMetadata,
     pub lines: Vec<LyricLine>,
+    // 纯文本歌词没有时间戳，不随播放滚动
+    pub synced: bool,
+    // 增强 LRC 的逐字时间戳 <mm:ss.xx>
+    pub word_timed: bool,
+    // 同步歌词中无法解析的行数
+    pub invalid_lines: usize,
+    // 已合并的翻译层名称，按配置的后缀顺序排列
+    pub layers: Vec<String>,
 }
 
-#[derive(Debug)]
+// LRC 头部标签，如 [ar:] [ti:] [al:] [length:] [offset:]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LyricsMetadata {
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub length: Option<Duration>,
+    // 毫秒，正值让歌词提前显示，与 [sync] offset_ms 含义相同
+    pub offset_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct LyricLine {
     pub timestamp: Duration,
     pub text: String,
+    // 翻译层名称 -> 该行的翻译文本
+    pub translations: BTreeMap<String, String>,
+}
+
+// 翻译层的显示方式，可按 t 键循环切换
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranslationMode {
+    Hidden,
+    Layer(String),
+    All,
+}
+
+impl TranslationMode {
+    // 配置中的 "all"、"none" 或某个翻译层名称
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "none" => Self::Hidden,
+            "all" | "" => Self::All,
+            layer => Self::Layer(layer.to_string()),
+        }
+    }
+
+    // 顺序为: 隐藏 -> 逐个翻译层 -> 全部 -> 隐藏，只有一层时跳过“全部”
+    pub fn next(&self, layers: &[String]) -> Self {
+        let position = match self {
+            Self::Hidden => 0,
+            Self::Layer(layer) => layers.iter().position(|l| l == layer).map_or(0, |i| i + 1),
+            Self::All if layers.len() > 1 => layers.len() + 1,
+            Self::All => layers.len(),
+        };
+        let count = if layers.len() > 1 {
+            layers.len() + 2
+        } else {
+            layers.len() + 1
+        };
+
+        match (position + 1) % count {
+            0 => Self::Hidden,
+            i if i <= layers.len() => Self::Layer(layers[i - 1].clone()),
+            _ => Self::All,
+        }
+    }
+
+    pub fn shows(&self, layer: &str) -> bool {
+        match self {
+            Self::Hidden => false,
+            Self::Layer(visible) => visible == layer,
+            Self::All => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_translation_mode() {
+        let layers = vec!["zh".to_string(), "romaji".to_string()];
+        let mut mode = TranslationMode::Hidden;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            mode = mode.next(&layers);
+            seen.push(mode.clone());
+        }
+        assert_eq!(
+            seen,
+            [
+                TranslationMode::Layer("zh".into()),
+                TranslationMode::Layer("romaji".into()),
+                TranslationMode::All,
+                TranslationMode::Hidden,
+            ]
+        );
+
+        let single = vec!["zh".to_string()];
+        assert_eq!(TranslationMode::All.next(&single), TranslationMode::Hidden);
+        assert_eq!(
+            TranslationMode::Hidden.next(&single),
+            TranslationMode::Layer("zh".into())
+        );
+        assert_eq!(
+            TranslationMode::Layer("zh".into()).next(&single),
+            TranslationMode::Hidden
+        );
+        assert!(TranslationMode::from_config("all").shows("zh"));
+        assert!(!TranslationMode::from_config("none").shows("zh"));
+    }
 }
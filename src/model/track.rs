@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use mpd::Song;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackInfo {
+    pub file: String,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    // 网络电台等流媒体，file 不变而 Title 随歌曲变化
+    pub stream: bool,
+}
+
+impl TrackInfo {
+    // 流媒体的同一个地址上标题变化也算切歌
+    pub fn same_track(&self, other: &TrackInfo) -> bool {
+        self.file == other.file
+            && (!self.stream || (self.artist == other.artist && self.title == other.title))
+    }
+}
+
+impl From<&Song> for TrackInfo {
+    fn from(song: &Song) -> Self {
+        let stream = song.file.contains("://");
+        let (artist, title) = match (&song.artist, &song.title) {
+            (None, Some(title)) if stream => split_stream_title(title),
+            (artist, title) => (artist.clone(), title.clone()),
+        };
+        Self {
+            file: song.file.clone(),
+            artist,
+            title,
+            album: song_tag(song, "Album"),
+            duration: song.duration,
+            stream,
+        }
+    }
+}
+
+// 电台的 ICY 标题通常为 "Artist - Title"
+fn split_stream_title(title: &str) -> (Option<String>, Option<String>) {
+    match title.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => (
+            Some(artist.trim().to_string()),
+            Some(title.trim().to_string()),
+        ),
+        _ => (None, Some(title.trim().to_string())),
+    }
+}
+
+fn song_tag(song: &Song, name: &str) -> Option<String> {
+    song.tags
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(file: &str, title: &str) -> Song {
+        Song {
+            file: file.into(),
+            title: Some(title.into()),
+            ..Song::default()
+        }
+    }
+
+    #[test]
+    fn test_stream_title() {
+        let track = TrackInfo::from(&song("http://radio.example/live", "Artist - Song - Live"));
+        assert!(track.stream);
+        assert_eq!(track.artist.as_deref(), Some("Artist"));
+        assert_eq!(track.title.as_deref(), Some("Song - Live"));
+
+        let next = TrackInfo::from(&song("http://radio.example/live", "Station Jingle"));
+        assert_eq!(next.artist, None);
+        assert_eq!(next.title.as_deref(), Some("Station Jingle"));
+        assert!(!track.same_track(&next));
+
+        let local = TrackInfo::from(&song("Artist/Song.flac", "A - B"));
+        assert!(!local.stream);
+        assert_eq!(local.title.as_deref(), Some("A - B"));
+        assert!(local.same_track(&TrackInfo::from(&song("Artist/Song.flac", "Other"))));
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+// 以最近一次MPD状态为锚点在本地推算播放进度，避免每帧都请求 status
+#[derive(Debug, Clone, Copy)]
+pub struct PlaybackClock {
+    elapsed: Duration,
+    duration: Option<Duration>,
+    anchor: Instant,
+    playing: bool,
+}
+
+impl PlaybackClock {
+    pub fn new(elapsed: Duration, duration: Option<Duration>, playing: bool) -> Self {
+        Self::new_at(elapsed, duration, playing, Instant::now())
+    }
+
+    fn new_at(
+        elapsed: Duration,
+        duration: Option<Duration>,
+        playing: bool,
+        anchor: Instant,
+    ) -> Self {
+        Self {
+            elapsed,
+            duration,
+            anchor,
+            playing,
+        }
+    }
+
+    pub fn sync(&mut self, elapsed: Duration, duration: Option<Duration>, playing: bool) {
+        *self = Self::new(elapsed, duration, playing);
+    }
+
+    // 本地发起跳转后立即更新，不必等待 idle 事件
+    pub fn seek(&mut self, elapsed: Duration) {
+        self.sync(elapsed, self.duration, self.playing);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    // 推算值不超过歌曲时长，切歌事件到达前停在结尾
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        if !self.playing {
+            return self.elapsed;
+        }
+        let elapsed = self.elapsed + now.saturating_duration_since(self.anchor);
+        self.duration
+            .map_or(elapsed, |duration| elapsed.min(duration))
+    }
+
+    pub fn since_sync(&self) -> Duration {
+        self.anchor.elapsed()
+    }
+}
+
+impl Default for PlaybackClock {
+    fn default() -> Self {
+        Self::new(Duration::ZERO, None, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_advances_while_playing() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(1500);
+        let secs = Duration::from_secs;
+
+        let playing = PlaybackClock::new_at(secs(10), Some(secs(100)), true, start);
+        assert_eq!(playing.elapsed_at(later), Duration::from_millis(11500));
+
+        let paused = PlaybackClock::new_at(secs(10), Some(secs(100)), false, start);
+        assert_eq!(paused.elapsed_at(later), secs(10));
+
+        let ending = PlaybackClock::new_at(secs(99), Some(secs(100)), true, start);
+        assert_eq!(ending.elapsed_at(later), secs(100));
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::Path,
+    process::Command,
+    sync::Mutex,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use anyhow::{Context, bail};
+use mpd::Client;
+
+use crate::model::config::{MpdAddress, MpdConfig, expand_tilde};
+
+#[derive(Debug)]
+pub enum MpdStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+pub type MpdClient = Client<MpdStream>;
+
+impl Read for MpdStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for MpdStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+impl MpdStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Self::Tcp(stream) => stream.try_clone().map(Self::Tcp),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.try_clone().map(Self::Unix),
+        }
+    }
+}
+
+// mpd 库没有 partition 命令，在空闲的连接上直接发送；此时客户端缓冲区为空
+fn select_partition(mut stream: MpdStream, partition: &str) -> anyhow::Result<()> {
+    let escaped = partition.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(stream, "partition \"{escaped}\"")?;
+    stream.flush()?;
+
+    let mut reply = Vec::new();
+    let mut byte = [0u8; 1];
+    while stream.read(&mut byte)? == 1 && byte[0] != b'\n' {
+        reply.push(byte[0]);
+    }
+    let reply = String::from_utf8_lossy(&reply);
+    if reply != "OK" {
+        bail!("切换到MPD分区 {partition} 失败: {reply}");
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn connect_socket(path: &Path) -> io::Result<MpdStream> {
+    #[cfg(target_os = "linux")]
+    if let Some(name) = path.to_str().and_then(|p| p.strip_prefix('@')) {
+        use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+        let addr = SocketAddr::from_abstract_name(name)?;
+        return UnixStream::connect_addr(&addr).map(MpdStream::Unix);
+    }
+    UnixStream::connect(path).map(MpdStream::Unix)
+}
+
+#[cfg(not(unix))]
+fn connect_socket(_path: &Path) -> io::Result<MpdStream> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "当前平台不支持 Unix 套接字",
+    ))
+}
+
+// 优先级：password_command > password_file > password，命令输出只取第一行
+fn resolve_password(config: &MpdConfig) -> anyhow::Result<String> {
+    if !config.password_command.is_empty() {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(&config.password_command)
+            .output()
+            .with_context(|| format!("执行密码命令失败: {}", config.password_command))?;
+        if !output.status.success() {
+            bail!(
+                "密码命令执行失败 ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Ok(stdout.lines().next().unwrap_or_default().to_string());
+    }
+
+    if !config.password_file.is_empty() {
+        let path = expand_tilde(&config.password_file);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("读取密码文件失败: {}", path.display()))?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    Ok(config.password.clone())
+}
+
+// 密码命令的输出按命令缓存，重连、空闲监听和切换服务器时不再重复执行
+static COMMAND_PASSWORDS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+fn cached_password(config: &MpdConfig) -> anyhow::Result<String> {
+    if config.password_command.is_empty() {
+        return resolve_password(config);
+    }
+    let mut cache = COMMAND_PASSWORDS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(password) = cache.get(&config.password_command) {
+        return Ok(password.clone());
+    }
+    let password = resolve_password(config)?;
+    cache.insert(config.password_command.clone(), password.clone());
+    Ok(password)
+}
+
+// 认证失败时丢弃缓存，下次连接重新执行命令以获取更新后的密码
+fn forget_password(config: &MpdConfig) {
+    COMMAND_PASSWORDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&config.password_command);
+}
+
+pub fn connect(config: &MpdConfig) -> anyhow::Result<MpdClient> {
+    let stream = match config.address() {
+        MpdAddress::Tcp(address) => TcpStream::connect(address).map(MpdStream::Tcp),
+        MpdAddress::Socket(path) => connect_socket(&path),
+    }
+    .with_context(|| format!("连接MPD服务失败: {}", config.get_address()))?;
+    let raw = stream.try_clone()?;
+    let mut client = Client::new(stream).with_context(|| "连接MPD服务失败")?;
+
+    let password = cached_password(config)?;
+    if !password.is_empty()
+        && let Err(e) = client.login(&password)
+    {
+        forget_password(config);
+        return Err(e).with_context(|| "MPD认证失败");
+    }
+    if !config.partition.is_empty() {
+        select_partition(raw, &config.partition)?;
+    }
+
+    Ok(client)
+}
+
+// 除 ACK 之外的错误说明连接已不可用，例如服务器按 connection_timeout 关闭了空闲连接
+pub fn is_connection_lost(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<mpd::error::Error>(),
+            Some(e) if !matches!(e, mpd::error::Error::Server(_))
+        ) || cause.is::<io::Error>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_password() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("mpd-password");
+        fs::write(&file, "from-file\n").unwrap();
+
+        let mut config = MpdConfig {
+            password: "plain".into(),
+            ..Default::default()
+        };
+        assert_eq!(resolve_password(&config).unwrap(), "plain");
+
+        config.password_file = file.to_string_lossy().into_owned();
+        assert_eq!(resolve_password(&config).unwrap(), "from-file");
+
+        config.password_command = "printf 'from-command\\nurl: mpd.local\\n'".into();
+        assert_eq!(resolve_password(&config).unwrap(), "from-command");
+
+        config.password_command = "echo denied >&2; exit 1".into();
+        let error = resolve_password(&config).unwrap_err().to_string();
+        assert!(error.contains("denied"));
+    }
+
+    #[test]
+    fn test_password_command_cached() {
+        let dir = tempdir().unwrap();
+        let runs = dir.path().join("runs");
+        let config = MpdConfig {
+            password_command: format!("echo run >> '{}'; echo secret", runs.display()),
+            ..Default::default()
+        };
+        assert_eq!(cached_password(&config).unwrap(), "secret");
+        assert_eq!(cached_password(&config).unwrap(), "secret");
+        assert_eq!(fs::read_to_string(&runs).unwrap().lines().count(), 1);
+
+        forget_password(&config);
+        assert_eq!(cached_password(&config).unwrap(), "secret");
+        assert_eq!(fs::read_to_string(&runs).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_is_connection_lost() {
+        let io_error = mpd::error::Error::Io(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(is_connection_lost(&anyhow::Error::from(io_error)));
+
+        let parse_error = mpd::error::Error::Parse(mpd::error::ParseError::BadPair);
+        assert!(is_connection_lost(
+            &anyhow::Error::from(parse_error).context("获取MPD状态失败")
+        ));
+
+        let ack = mpd::error::Error::Server(mpd::error::ServerError {
+            code: mpd::error::ErrorCode::NoExist,
+            pos: 0,
+            command: "seekcur".into(),
+            detail: "Not seekable".into(),
+        });
+        assert!(!is_connection_lost(&anyhow::Error::from(ack)));
+        assert!(!is_connection_lost(&anyhow::anyhow!("当前没有播放")));
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use mpd::State as MpdState;
+
+use crate::player::connection::MpdClient;
+
+const VOLUME_STEP: i8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerAction {
+    TogglePause,
+    Next,
+    Previous,
+    // 相对当前位置的秒数，负值为后退
+    Seek(i64),
+    SeekTo(Duration),
+    VolumeUp,
+    VolumeDown,
+    ToggleRepeat,
+    ToggleRandom,
+    ToggleSingle,
+    ToggleConsume,
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "开" } else { "关" }
+}
+
+fn format_time(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+// 跳转目标限制在歌曲范围内
+fn seek_target(elapsed: Duration, duration: Option<Duration>, delta: i64) -> Duration {
+    let step = Duration::from_secs(delta.unsigned_abs());
+    let target = if delta >= 0 {
+        elapsed + step
+    } else {
+        elapsed.saturating_sub(step)
+    };
+    duration.map_or(target, |duration| target.min(duration))
+}
+
+impl PlayerAction {
+    // 执行操作并返回界面上显示的提示
+    pub fn apply(self, client: &mut MpdClient) -> anyhow::Result<String> {
+        let status = client.status()?;
+        let message = match self {
+            Self::TogglePause => match status.state {
+                MpdState::Stop => {
+                    client.play()?;
+                    "播放".to_string()
+                }
+                MpdState::Play => {
+                    client.pause(true)?;
+                    "暂停".to_string()
+                }
+                MpdState::Pause => {
+                    client.pause(false)?;
+                    "继续播放".to_string()
+                }
+            },
+            Self::Next => {
+                client.next()?;
+                "下一首".to_string()
+            }
+            Self::Previous => {
+                client.prev()?;
+                "上一首".to_string()
+            }
+            Self::Seek(delta) => {
+                if status.state == MpdState::Stop {
+                    bail!("当前没有播放");
+                }
+                let elapsed = status.elapsed.unwrap_or_default();
+                let target = seek_target(elapsed, status.duration, delta);
+                client.rewind(target)?;
+                let direction = if delta >= 0 { "快进" } else { "快退" };
+                format!(
+                    "{direction} {} 秒 ({})",
+                    delta.unsigned_abs(),
+                    format_time(target)
+                )
+            }
+            Self::SeekTo(target) => {
+                if status.state == MpdState::Stop {
+                    bail!("当前没有播放");
+                }
+                client.rewind(target)?;
+                format!("跳转到 {}", format_time(target))
+            }
+            Self::VolumeUp | Self::VolumeDown => {
+                // 没有混音器的输出返回 -1
+                if status.volume < 0 {
+                    bail!("当前输出不支持音量调节");
+                }
+                let step = if self == Self::VolumeUp {
+                    VOLUME_STEP
+                } else {
+                    -VOLUME_STEP
+                };
+                let volume = (status.volume + step).clamp(0, 100);
+                client.volume(volume)?;
+                format!("音量 {volume}%")
+            }
+            Self::ToggleRepeat => {
+                client.repeat(!status.repeat)?;
+                format!("循环播放: {}", on_off(!status.repeat))
+            }
+            Self::ToggleRandom => {
+                client.random(!status.random)?;
+                format!("随机播放: {}", on_off(!status.random))
+            }
+            Self::ToggleSingle => {
+                client.single(!status.single)?;
+                format!("单曲播放: {}", on_off(!status.single))
+            }
+            Self::ToggleConsume => {
+                client.consume(!status.consume)?;
+                format!("播放后删除: {}", on_off(!status.consume))
+            }
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_target() {
+        let secs = Duration::from_secs;
+        assert_eq!(seek_target(secs(10), Some(secs(100)), 5), secs(15));
+        assert_eq!(seek_target(secs(3), Some(secs(100)), -5), secs(0));
+        assert_eq!(seek_target(secs(90), Some(secs(100)), 30), secs(100));
+        assert_eq!(seek_target(secs(90), None, 30), secs(120));
+    }
+}
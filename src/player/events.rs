@@ -0,0 +1,67 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use anyhow::Context;
+use mpd::{Idle, Song, Status, idle::Subsystem};
+
+use crate::{model::config::MpdConfig, player::connection::connect};
+
+#[derive(Debug)]
+pub struct PlayerSnapshot {
+    pub status: Status,
+    pub song: Option<Song>,
+    // 已启用的输出名称
+    pub outputs: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum PlayerEvent {
+    Changed(Box<PlayerSnapshot>),
+    Error(String),
+}
+
+const SUBSYSTEMS: [Subsystem; 3] = [Subsystem::Player, Subsystem::Options, Subsystem::Output];
+
+// 在独立连接上等待 idle 事件，每次变化后读取一次状态、当前歌曲和已启用的输出
+pub fn spawn_idle_listener(config: &MpdConfig) -> anyhow::Result<Receiver<PlayerEvent>> {
+    let mut client = connect(config)?;
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        loop {
+            let snapshot = client
+                .status()
+                .context("获取MPD状态失败")
+                .and_then(|status| {
+                    let song = client.currentsong().context("获取当前歌曲失败")?;
+                    let outputs = client.outputs().context("获取MPD输出失败")?;
+                    Ok(PlayerSnapshot {
+                        status,
+                        song,
+                        outputs: outputs
+                            .into_iter()
+                            .filter(|output| output.enabled)
+                            .map(|output| output.name)
+                            .collect(),
+                    })
+                });
+            let event = match snapshot {
+                Ok(snapshot) => PlayerEvent::Changed(Box::new(snapshot)),
+                Err(e) => PlayerEvent::Error(format!("{e:#}")),
+            };
+            let failed = matches!(event, PlayerEvent::Error(_));
+            if sender.send(event).is_err() || failed {
+                break;
+            }
+
+            if let Err(e) = client.wait(&SUBSYSTEMS) {
+                let _ = sender.send(PlayerEvent::Error(format!("等待MPD事件失败: {e}")));
+                break;
+            }
+        }
+    });
+
+    Ok(receiver)
+}
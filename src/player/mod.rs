@@ -0,0 +1,5 @@
+pub mod clock;
+pub mod connection;
+pub mod control;
+pub mod events;
+pub mod session;
@@ -0,0 +1,102 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use mpd::{Song, Status};
+
+use crate::{
+    model::config::MpdConfig,
+    player::{
+        connection::{MpdClient, connect},
+        events::{PlayerEvent, spawn_idle_listener},
+    },
+};
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected { error: String, retry_at: Instant },
+}
+
+impl ConnectionState {
+    // 每次失败后等待时间翻倍，最长 30 秒
+    pub fn disconnected(error: String, attempts: u32) -> Self {
+        let delay = INITIAL_RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(MAX_RETRY_DELAY);
+        Self::Disconnected {
+            error,
+            retry_at: Instant::now() + delay,
+        }
+    }
+
+    pub fn should_retry(&self) -> bool {
+        match self {
+            Self::Disconnected { retry_at, .. } => Instant::now() >= *retry_at,
+            _ => false,
+        }
+    }
+}
+
+// 一次成功的连接：命令连接、初始状态和事件连接
+pub struct Session {
+    pub client: MpdClient,
+    pub status: Status,
+    pub song: Option<Song>,
+    pub events: Receiver<PlayerEvent>,
+}
+
+impl Session {
+    pub fn open(config: &MpdConfig) -> anyhow::Result<Self> {
+        let mut client = connect(config)?;
+        let status = client.status().context("获取MPD状态失败")?;
+        let song = client.currentsong().context("获取当前播放歌曲失败")?;
+        let events = spawn_idle_listener(config)?;
+        Ok(Self {
+            client,
+            status,
+            song,
+            events,
+        })
+    }
+}
+
+// 连接在后台线程中建立，MPD无响应时界面仍可操作
+pub fn spawn_connect(config: MpdConfig) -> Receiver<anyhow::Result<Session>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(Session::open(&config));
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delay(attempts: u32) -> Duration {
+        match ConnectionState::disconnected(String::new(), attempts) {
+            ConnectionState::Disconnected { retry_at, .. } => {
+                retry_at.saturating_duration_since(Instant::now())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let secs = |s| Duration::from_secs(s);
+        assert!(delay(1) <= secs(1) && delay(1) > secs(0));
+        assert!(delay(3) <= secs(4) && delay(3) > secs(3));
+        assert!(delay(6) <= secs(30) && delay(6) > secs(29));
+        assert!(delay(40) > secs(29));
+        assert!(!ConnectionState::disconnected(String::new(), 1).should_retry());
+    }
+}
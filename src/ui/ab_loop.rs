@@ -0,0 +1,138 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use crate::model::config::AbLoopConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Playing,
+    // 两遍之间的停顿，到时间后继续播放
+    Gap(Instant),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopStep {
+    Continue,
+    Seek(Duration),
+    PauseAndSeek(Duration),
+    Resume,
+    Finished,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbLoop {
+    pub start_line: usize,
+    // 只标记了 A 点时为 None，循环尚未生效
+    pub end_line: Option<usize>,
+    // 0 表示无限循环
+    pub repeat: u32,
+    pub completed: u32,
+    gap: Duration,
+    phase: Phase,
+}
+
+impl AbLoop {
+    pub fn new(start_line: usize, config: &AbLoopConfig) -> Self {
+        Self {
+            start_line,
+            end_line: None,
+            repeat: config.repeat,
+            completed: 0,
+            gap: Duration::from_millis(config.gap_ms),
+            phase: Phase::Playing,
+        }
+    }
+
+    pub fn in_gap(&self) -> bool {
+        matches!(self.phase, Phase::Gap(_))
+    }
+
+    // range 为 A 点到 B 行结束的播放时间
+    pub fn tick(&mut self, elapsed: Duration, range: Range<Duration>, now: Instant) -> LoopStep {
+        if self.end_line.is_none() {
+            return LoopStep::Continue;
+        }
+
+        match self.phase {
+            Phase::Gap(resume_at) if now >= resume_at => {
+                self.phase = Phase::Playing;
+                LoopStep::Resume
+            }
+            Phase::Gap(_) => LoopStep::Continue,
+            Phase::Playing if elapsed >= range.end => {
+                self.completed += 1;
+                if self.repeat > 0 && self.completed >= self.repeat {
+                    return LoopStep::Finished;
+                }
+                if self.gap.is_zero() {
+                    LoopStep::Seek(range.start)
+                } else {
+                    self.phase = Phase::Gap(now + self.gap);
+                    LoopStep::PauseAndSeek(range.start)
+                }
+            }
+            Phase::Playing => LoopStep::Continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ab_loop(repeat: u32, gap_ms: u64) -> AbLoop {
+        let mut ab_loop = AbLoop::new(2, &AbLoopConfig { repeat, gap_ms });
+        ab_loop.end_line = Some(4);
+        ab_loop
+    }
+
+    #[test]
+    fn test_loop_repeats_then_finishes() {
+        let secs = Duration::from_secs;
+        let now = Instant::now();
+        let mut ab_loop = ab_loop(2, 0);
+
+        assert_eq!(
+            ab_loop.tick(secs(15), secs(10)..secs(20), now),
+            LoopStep::Continue
+        );
+        assert_eq!(
+            ab_loop.tick(secs(20), secs(10)..secs(20), now),
+            LoopStep::Seek(secs(10))
+        );
+        assert_eq!(
+            ab_loop.tick(secs(21), secs(10)..secs(20), now),
+            LoopStep::Finished
+        );
+
+        let mut pending = AbLoop::new(2, &AbLoopConfig::default());
+        assert_eq!(
+            pending.tick(secs(30), secs(10)..secs(20), now),
+            LoopStep::Continue
+        );
+    }
+
+    #[test]
+    fn test_loop_gap() {
+        let secs = Duration::from_secs;
+        let now = Instant::now();
+        let mut ab_loop = ab_loop(0, 1500);
+
+        assert_eq!(
+            ab_loop.tick(secs(20), secs(10)..secs(20), now),
+            LoopStep::PauseAndSeek(secs(10))
+        );
+        assert!(ab_loop.in_gap());
+        assert_eq!(
+            ab_loop.tick(secs(10), secs(10)..secs(20), now + secs(1)),
+            LoopStep::Continue
+        );
+        assert_eq!(
+            ab_loop.tick(secs(10), secs(10)..secs(20), now + secs(2)),
+            LoopStep::Resume
+        );
+        assert_eq!(ab_loop.completed, 1);
+    }
+}
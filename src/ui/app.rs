@@ -1,68 +1,312 @@
-use mpd::{Client, State as MpdState};
-use std::time::Duration;
+use mpd::{Song, State as MpdState, Status};
+use std::{
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use crate::model::{config::Config, lyrics::Lyrics};
+use crate::{
+    config::overrides::resolve_track_config,
+    lyrics::{
+        mapping::LyricsBinding,
+        offset::load_offset,
+        provider::{LyricsCandidate, Providers, providers_for_track},
+        ranking::ScoredCandidate,
+    },
+    model::{
+        config::{Config, SyncConfig},
+        lyrics::{Lyrics, TranslationMode},
+        track::TrackInfo,
+    },
+    player::{clock::PlaybackClock, connection::MpdClient, session::ConnectionState},
+    ui::{ab_loop::AbLoop, picker::LyricsPicker},
+};
 
 #[derive(Debug)]
 pub struct App {
     pub config: Config,
-    pub mpd_client: Client,
+    // 合并目录中 .lyra.toml 后当前歌曲实际使用的配置
+    pub track_config: Config,
+    pub providers: Providers,
+    // 连接断开期间为 None
+    pub mpd_client: Option<MpdClient>,
+    pub connection: ConnectionState,
+    // 当前使用的 [mpd.profiles.<name>] 服务器，None 为 [mpd] 本身
+    pub server: Option<String>,
     pub current_lyrics: Option<Lyrics>,
-    pub current_song_file_name: Option<String>,
-    pub current_song_title: Option<String>,
+    pub lyrics_source: Option<&'static str>,
+    // 当前歌词来自 LRC 文件时的路径，用于写入 [offset:] 标签
+    pub lyrics_file: Option<PathBuf>,
+    pub lyrics_loading: bool,
+    // 本次查找到的全部候选及评分，供调试视图查看
+    pub lyrics_ranking: Vec<ScoredCandidate>,
+    pub current_track: Option<TrackInfo>,
     pub scroll_offset: usize,
+    // j/k 移动的选择行，为 None 时跟随播放进度
+    pub line_cursor: Option<usize>,
+    pub ab_loop: Option<AbLoop>,
+    // 按 +/- 调整的单曲偏移，叠加在 [sync] offset_ms 上
+    pub track_offset_ms: i64,
+    pub saved_offset_ms: i64,
+    // 已启用的 MPD 输出，用于计算 [output_latency] 补偿
+    pub outputs: Vec<String>,
     pub play_state: MpdState,
+    pub clock: PlaybackClock,
+    // 电台流的 elapsed 从开始收听算起，以标题变化时的进度作为歌曲开头
+    pub track_start: Duration,
+    pub picker: Option<LyricsPicker>,
+    // 按 t 键切换后的翻译显示方式，未切换时使用配置
+    pub translation_override: Option<TranslationMode>,
+    pub show_debug: bool,
+    // 播放控制等操作的短暂提示
+    pub notice: Option<(String, Instant)>,
     pub should_quit: bool,
 }
 
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+
 impl App {
-    pub fn new(
-        config: Config,
-        mpd_client: Client,
-        initial_state: MpdState,
-        current_lyrics: Option<Lyrics>,
-        current_song_file_name: Option<String>,
-        current_song_title: Option<String>,
-    ) -> Self {
+    pub fn new(config: Config, providers: Providers, server: Option<String>) -> Self {
         Self {
+            track_config: config.clone(),
             config,
-            mpd_client,
-            current_lyrics,
-            current_song_file_name,
-            current_song_title,
+            providers,
+            mpd_client: None,
+            connection: ConnectionState::Connecting,
+            server,
+            current_lyrics: None,
+            lyrics_source: None,
+            lyrics_file: None,
+            lyrics_loading: false,
+            lyrics_ranking: Vec::new(),
+            current_track: None,
             scroll_offset: 0,
-            play_state: initial_state,
+            line_cursor: None,
+            ab_loop: None,
+            track_offset_ms: 0,
+            saved_offset_ms: 0,
+            outputs: Vec::new(),
+            play_state: MpdState::Stop,
+            clock: PlaybackClock::default(),
+            track_start: Duration::ZERO,
+            picker: None,
+            translation_override: None,
+            show_debug: false,
+            notice: None,
             should_quit: false,
         }
     }
 
-    pub fn update_current_song_name(&mut self, new_song_file_name: Option<String>) -> bool {
-        let changed = self.current_song_file_name != new_song_file_name;
+    // 返回歌曲是否变化，重新连接后歌曲未变时沿用已有的歌词和界面状态
+    pub fn attach(&mut self, client: MpdClient, status: &Status, song: Option<&Song>) -> bool {
+        self.mpd_client = Some(client);
+        self.connection = ConnectionState::Connected;
+        self.sync_clock(status);
+        self.update_current_track(song.map(TrackInfo::from))
+    }
+
+    // 断开后停在当前进度，直到重新连接
+    pub fn detach(&mut self, connection: ConnectionState) {
+        self.mpd_client = None;
+        self.connection = connection;
+        self.play_state = MpdState::Stop;
+        self.clock.sync(self.clock.elapsed(), None, false);
+    }
+
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.notice = Some((message.into(), Instant::now()));
+    }
+
+    pub fn current_notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < NOTICE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    pub fn sync_clock(&mut self, status: &Status) {
+        self.play_state = status.state;
+        self.clock.sync(
+            status.elapsed.unwrap_or_default(),
+            status.duration,
+            status.state == MpdState::Play,
+        );
+    }
+
+    pub fn update_current_track(&mut self, new_track: Option<TrackInfo>) -> bool {
+        let changed = match (&self.current_track, &new_track) {
+            (Some(current), Some(new)) => !current.same_track(new),
+            (current, new) => current.is_some() != new.is_some(),
+        };
         if changed {
-            self.current_song_file_name = new_song_file_name;
+            self.track_start = match &new_track {
+                Some(track) if track.stream => self.clock.elapsed(),
+                _ => Duration::ZERO,
+            };
+            self.track_config = match &new_track {
+                Some(track) => {
+                    let (config, warnings) = resolve_track_config(&self.config, track);
+                    if !warnings.is_empty() {
+                        self.notify(warnings.join("；"));
+                    }
+                    config
+                }
+                None => self.config.clone(),
+            };
+            self.current_track = new_track;
             self.scroll_offset = 0;
+            self.picker = None;
         }
         changed
     }
 
-    pub fn update_scroll_offset(&mut self) -> Result<(), mpd::error::Error> {
-        if self.play_state != MpdState::Play || self.current_lyrics.is_none() {
-            return Ok(());
+    pub fn track_providers(&self) -> Providers {
+        providers_for_track(&self.config, &self.providers, &self.track_config)
+    }
+
+    pub fn set_lyrics(&mut self, candidate: Option<LyricsCandidate>) {
+        self.lyrics_loading = false;
+        self.lyrics_source = candidate.as_ref().map(|c| c.source);
+        self.lyrics_file = match candidate.as_ref().and_then(|c| c.binding.as_ref()) {
+            Some(LyricsBinding::File { path }) => Some(path.clone()),
+            _ => None,
+        };
+        self.current_lyrics = candidate.map(|c| c.lyrics);
+        self.scroll_offset = 0;
+        self.line_cursor = None;
+        self.ab_loop = None;
+        self.restore_offset();
+    }
+
+    // 已保存的偏移优先，否则使用歌词自带的 [offset:] 标签；电台流的地址不能区分歌曲，只使用标签
+    fn restore_offset(&mut self) {
+        let Some(lyrics) = &self.current_lyrics else {
+            self.track_offset_ms = 0;
+            self.saved_offset_ms = 0;
+            return;
+        };
+        let tag = lyrics.metadata.offset_ms.unwrap_or_default();
+        let stored = self
+            .current_track
+            .as_ref()
+            .filter(|t| !t.stream)
+            .and_then(|track| {
+                load_offset(
+                    self.track_config.sync.save_to,
+                    &track.file,
+                    self.mpd_client.as_mut(),
+                )
+            });
+        self.track_offset_ms = stored.unwrap_or(tag);
+        self.saved_offset_ms = self.track_offset_ms;
+    }
+
+    pub fn adjust_offset(&mut self, delta_ms: i64) {
+        self.track_offset_ms += delta_ms;
+        self.notify(format!("歌词偏移: {:+} ms", self.track_offset_ms));
+    }
+
+    pub fn set_outputs(&mut self, outputs: Vec<String>) {
+        if outputs == self.outputs {
+            return;
+        }
+        let previous = self.config.output_latency(&self.outputs);
+        self.outputs = outputs;
+        let latency = self.config.output_latency(&self.outputs);
+        if latency != previous {
+            self.notify(format!("输出延迟补偿: {} ms", latency.as_millis()));
+        }
+    }
+
+    // 当前歌曲实际使用的时间偏移，也用于计算跳转目标，不含输出延迟
+    pub fn sync(&self) -> SyncConfig {
+        SyncConfig {
+            offset_ms: self.track_config.sync.offset_ms + self.track_offset_ms,
+            ..self.track_config.sync.clone()
         }
+    }
 
-        let status = self.mpd_client.status()?;
-        let current_play_time = status.elapsed.unwrap_or(Duration::ZERO);
+    pub fn move_cursor(&mut self, delta: isize) {
+        let Some(lyrics) = self.current_lyrics.as_ref().filter(|l| !l.lines.is_empty()) else {
+            return;
+        };
+        let current = self.line_cursor.unwrap_or(self.scroll_offset);
+        let last = lyrics.lines.len() - 1;
+        self.line_cursor = Some(current.saturating_add_signed(delta).min(last));
+    }
 
-        let lyrics = self.current_lyrics.as_ref().unwrap();
+    // A 行开始到 B 行结束(下一行开始或歌曲结尾)对应的播放时间
+    pub fn loop_range(&self) -> Option<Range<Duration>> {
+        let ab_loop = self.ab_loop.as_ref()?;
+        let lines = &self.current_lyrics.as_ref()?.lines;
+        let sync = self.sync();
 
-        let target_offset = lyrics
-            .lines
-            .iter()
-            .enumerate()
-            .find(|(_, line)| line.timestamp > current_play_time)
-            .map_or(lyrics.lines.len().saturating_sub(1), |(i, _)| {
-                i.saturating_sub(1)
-            });
+        let start = sync.playback_time(lines.get(ab_loop.start_line)?.timestamp);
+        let end = match lines.get(ab_loop.end_line? + 1) {
+            Some(next) => sync.playback_time(next.timestamp),
+            None => self.current_track.as_ref()?.duration?,
+        };
+        Some(start..end)
+    }
+
+    // 当前歌曲内的播放进度
+    pub fn position(&self) -> Duration {
+        self.clock.elapsed().saturating_sub(self.track_start)
+    }
+
+    // 实际听到的进度，输出有延迟时晚于 MPD 的 elapsed；跳转目标不减去延迟
+    pub fn audible_position(&self) -> Duration {
+        self.position()
+            .saturating_sub(self.config.output_latency(&self.outputs))
+    }
+
+    pub fn is_stream(&self) -> bool {
+        self.current_track.as_ref().is_some_and(|t| t.stream)
+    }
+
+    // 按播放进度计算当前歌词行，纯文本歌词没有当前行
+    pub fn playing_line(&self) -> Option<usize> {
+        let lyrics = self.current_lyrics.as_ref().filter(|l| l.synced)?;
+        let current_play_time = self.sync().apply(self.audible_position());
+        Some(
+            lyrics
+                .lines
+                .iter()
+                .position(|line| line.timestamp > current_play_time)
+                .map_or(lyrics.lines.len().saturating_sub(1), |i| {
+                    i.saturating_sub(1)
+                }),
+        )
+    }
+
+    pub fn set_ranked_lyrics(&mut self, ranking: Vec<ScoredCandidate>) {
+        self.set_lyrics(ranking.first().map(|s| s.candidate.clone()));
+        self.lyrics_ranking = ranking;
+    }
+
+    pub fn translation_mode(&self) -> TranslationMode {
+        self.translation_override.clone().unwrap_or_else(|| {
+            TranslationMode::from_config(&self.track_config.lyric_style.translation)
+        })
+    }
+
+    pub fn cycle_translation(&mut self) {
+        let Some(lyrics) = &self.current_lyrics else {
+            return;
+        };
+        if lyrics.layers.is_empty() {
+            return;
+        }
+        self.translation_override = Some(self.translation_mode().next(&lyrics.layers));
+    }
+
+    pub fn update_scroll_offset(&mut self) {
+        if self.play_state != MpdState::Play {
+            return;
+        }
+        let Some(target_offset) = self.playing_line() else {
+            return;
+        };
 
         let diff = match self.scroll_offset < target_offset {
             true => target_offset - self.scroll_offset,
@@ -87,6 +331,5 @@ impl App {
                 self.scroll_offset = target_offset;
             }
         }
-        Ok(())
     }
 }
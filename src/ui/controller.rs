@@ -1,75 +1,551 @@
-use anyhow::{Context, Ok, Result};
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use anyhow::{Ok, Result, anyhow};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use mpd::State as MpdState;
+use std::collections::HashSet;
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::time::{Duration, Instant};
 
-use crate::lyrics::parser::find_lyrics_for_track;
-use crate::ui::app::App;
+use crate::config::paths::get_missing_log_path;
+use crate::lyrics::lookup::{LookupResult, spawn_lookup};
+use crate::lyrics::missing::{MissingRecord, append_record};
+use crate::lyrics::offset::save_offset;
+use crate::lyrics::parser::{bind_lyrics, list_candidates};
+use crate::lyrics::provider::build_providers;
+use crate::lyrics::translation::attach_translations;
+use crate::model::{
+    config::{MpdConfig, OffsetStore},
+    track::TrackInfo,
+};
+use crate::player::connection::is_connection_lost;
+use crate::player::control::PlayerAction;
+use crate::player::events::{PlayerEvent, PlayerSnapshot};
+use crate::player::session::{ConnectionState, Session, spawn_connect};
+use crate::ui::{
+    ab_loop::{AbLoop, LoopStep},
+    app::App,
+    picker::LyricsPicker,
+};
+
+// 播放中每隔一段时间用 status 校准本地时钟，其余时间只在事件到达时同步
+const RESYNC_INTERVAL: Duration = Duration::from_secs(5);
+// 小于 MPD 默认 connection_timeout 的 60 秒
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+const OFFSET_STEP_MS: i64 = 100;
 
 pub struct Controller {
     pub app: App,
-    last_mpd_check: Instant,
-    mpd_poll_interval: Duration,
+    // 包含全部命名服务器的原始 [mpd] 配置
+    servers: MpdConfig,
+    player_events: Option<Receiver<PlayerEvent>>,
+    connecting: Option<Receiver<Result<Session>>>,
+    failed_attempts: u32,
+    last_resync: Instant,
+    lyrics_lookup: Option<Receiver<LookupResult>>,
+    // 只为切歌触发的查找记录缺少歌词，重连和切换服务器后的重新查找不重复记录
+    record_missing: bool,
+    // 本次运行中已记录过的歌曲
+    missing_logged: HashSet<String>,
 }
 
 impl Controller {
-    pub fn new(app: App) -> Self {
-        Self {
+    pub fn new(app: App, servers: MpdConfig) -> Self {
+        let mut controller = Self {
             app,
-            last_mpd_check: Instant::now(),
-            mpd_poll_interval: Duration::from_millis(500),
-        }
+            servers,
+            player_events: None,
+            connecting: None,
+            failed_attempts: 0,
+            last_resync: Instant::now(),
+            lyrics_lookup: None,
+            record_missing: false,
+            missing_logged: HashSet::new(),
+        };
+        controller.connect();
+        controller
     }
 
     pub fn run(&mut self) -> Result<()> {
         self.handle_user_input()?;
-
-        if self.last_mpd_check.elapsed() >= self.mpd_poll_interval {
-            self.handle_mpd_state()?;
-            self.last_mpd_check = Instant::now();
+        self.handle_connection();
+        self.handle_lyrics_lookup();
+        if let Err(e) = self.handle_player_events() {
+            self.disconnect(e);
         }
+        self.handle_ab_loop();
+        self.keep_alive();
 
         if self.app.play_state == MpdState::Play {
-            self.app.update_scroll_offset()?;
+            self.app.update_scroll_offset();
         }
 
         Ok(())
     }
 
     pub fn handle_user_input(&mut self) -> Result<()> {
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(event) = event::read()? {
-                self.handle_key_event(event);
-            }
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(event) = event::read()?
+        {
+            self.handle_key_event(event);
         }
         Ok(())
     }
 
     pub fn handle_key_event(&mut self, event: KeyEvent) {
-        if let KeyCode::Char('q') = event.code {
-            self.app.should_quit = true;
+        if self.app.picker.is_some() {
+            self.handle_picker_key_event(event);
+            return;
+        }
+
+        match event.code {
+            KeyCode::Char('q') => self.app.should_quit = true,
+            KeyCode::Char('l') => self.open_picker(),
+            KeyCode::Char('t') => self.app.cycle_translation(),
+            KeyCode::Char('D') => self.app.show_debug = !self.app.show_debug,
+            KeyCode::Char('S') => self.switch_server(),
+            KeyCode::Char(' ') => self.control(PlayerAction::TogglePause),
+            KeyCode::Char('>') => self.control(PlayerAction::Next),
+            KeyCode::Char('<') => self.control(PlayerAction::Previous),
+            KeyCode::Left | KeyCode::Right => {
+                let step = if event.modifiers.contains(KeyModifiers::SHIFT) {
+                    30
+                } else {
+                    5
+                };
+                let delta = if event.code == KeyCode::Left {
+                    -step
+                } else {
+                    step
+                };
+                self.control(PlayerAction::Seek(delta));
+            }
+            KeyCode::Char('0') => self.control(PlayerAction::VolumeUp),
+            KeyCode::Char('9') => self.control(PlayerAction::VolumeDown),
+            KeyCode::Char('r') => self.control(PlayerAction::ToggleRepeat),
+            KeyCode::Char('z') => self.control(PlayerAction::ToggleRandom),
+            KeyCode::Char('y') => self.control(PlayerAction::ToggleSingle),
+            KeyCode::Char('R') => self.control(PlayerAction::ToggleConsume),
+            KeyCode::Esc => {
+                self.app.show_debug = false;
+                self.app.line_cursor = None;
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.app.move_cursor(1),
+            KeyCode::Char('k') | KeyCode::Up => self.app.move_cursor(-1),
+            KeyCode::Enter => {
+                if let Some(index) = self.app.line_cursor.take() {
+                    self.seek_to_line(index);
+                }
+            }
+            KeyCode::Char('a') => self.mark_loop_start(),
+            KeyCode::Char('b') => self.mark_loop_end(),
+            KeyCode::Char('x') => self.clear_loop(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.adjust_offset(OFFSET_STEP_MS),
+            KeyCode::Char('-') => self.adjust_offset(-OFFSET_STEP_MS),
+            KeyCode::Char('W') => self.save_offset(),
+            KeyCode::Char(']') => {
+                if let Some(current) = self.app.playing_line() {
+                    self.seek_to_line(current + 1);
+                }
+            }
+            KeyCode::Char('[') => {
+                if let Some(current) = self.app.playing_line() {
+                    self.seek_to_line(current.saturating_sub(1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 状态变化由 idle 事件同步回来，这里只给出提示
+    fn control(&mut self, action: PlayerAction) {
+        let Some(client) = self.app.mpd_client.as_mut() else {
+            self.app.notify("MPD 未连接");
+            return;
+        };
+        match action.apply(client) {
+            Result::Ok(message) => self.app.notify(message),
+            Err(e) if is_connection_lost(&e) => self.disconnect(e),
+            Err(e) => self.app.notify(format!("操作失败: {e:#}")),
         }
     }
 
-    pub fn handle_mpd_state(&mut self) -> Result<()> {
-        let status = self.app.mpd_client.status().context("获取MPD状态失败")?;
-        let current_song = self
-            .app
-            .mpd_client
-            .currentsong()
-            .context("获取当前歌曲失败")?;
+    // 选择行优先，否则使用当前播放行
+    fn loop_line(&mut self) -> Option<usize> {
+        if self.app.is_stream() {
+            self.app.notify("电台流无法跳转，不支持 A-B 循环");
+            return None;
+        }
+        let line = self.app.line_cursor.take().or(self.app.playing_line());
+        if line.is_none() {
+            self.app.notify("A-B 循环需要同步歌词");
+        }
+        line
+    }
 
-        self.app.play_state = status.state;
+    fn mark_loop_start(&mut self) {
+        if let Some(line) = self.loop_line() {
+            self.app.ab_loop = Some(AbLoop::new(line, &self.app.track_config.ab_loop));
+            self.app
+                .notify(format!("A 点: 第 {} 行，按 b 设置 B 点", line + 1));
+        }
+    }
 
-        let new_song_file = current_song.as_ref().map(|s| s.file.clone());
-        if self.app.update_current_song_name(new_song_file.clone()) {
-            self.app.current_song_title = current_song.and_then(|s| s.title);
-            self.app.current_lyrics = new_song_file
-                .as_ref()
-                .and_then(|file| find_lyrics_for_track(&self.app.config.paths, file).ok())
-                .flatten();
+    fn mark_loop_end(&mut self) {
+        let Some(start_line) = self.app.ab_loop.as_ref().map(|l| l.start_line) else {
+            self.app.notify("请先按 a 设置 A 点");
+            return;
+        };
+        let Some(line) = self.loop_line() else {
+            return;
+        };
+        if line < start_line {
+            self.app.notify("B 点不能早于 A 点");
+            return;
+        }
+        if let Some(ab_loop) = self.app.ab_loop.as_mut() {
+            ab_loop.end_line = Some(line);
         }
+        self.app
+            .notify(format!("A-B 循环: 第 {}-{} 行", start_line + 1, line + 1));
+    }
 
-        Ok(())
+    fn clear_loop(&mut self) {
+        let Some(ab_loop) = self.app.ab_loop.take() else {
+            return;
+        };
+        // 停顿期间取消时恢复播放
+        if ab_loop.in_gap()
+            && let Some(client) = self.app.mpd_client.as_mut()
+        {
+            let _ = client.pause(false);
+        }
+        self.app.notify("已取消 A-B 循环");
+    }
+
+    fn handle_ab_loop(&mut self) {
+        let Some(range) = self.app.loop_range() else {
+            return;
+        };
+        // 按实际听到的进度判断 B 行是否结束
+        let elapsed = self.app.audible_position();
+        let playing = self.app.play_state == MpdState::Play;
+        let (Some(ab_loop), Some(client)) =
+            (self.app.ab_loop.as_mut(), self.app.mpd_client.as_mut())
+        else {
+            return;
+        };
+        if !playing && !ab_loop.in_gap() {
+            return;
+        }
+
+        let result = match ab_loop.tick(elapsed, range, Instant::now()) {
+            LoopStep::Continue => return,
+            LoopStep::Finished => {
+                self.app.ab_loop = None;
+                self.app.notify("A-B 循环已完成");
+                return;
+            }
+            LoopStep::Seek(start) => client.rewind(start).map(|_| self.app.clock.seek(start)),
+            LoopStep::PauseAndSeek(start) => client
+                .pause(true)
+                .and_then(|_| client.rewind(start))
+                .map(|_| self.app.clock.seek(start)),
+            LoopStep::Resume => client.pause(false),
+        };
+        if let Err(e) = result {
+            let error = anyhow::Error::from(e);
+            if is_connection_lost(&error) {
+                self.disconnect(error);
+            } else {
+                self.app.notify(format!("A-B 循环跳转失败: {error}"));
+            }
+        }
+    }
+
+    fn adjust_offset(&mut self, delta_ms: i64) {
+        match &self.app.current_lyrics {
+            Some(lyrics) if lyrics.synced => self.app.adjust_offset(delta_ms),
+            Some(_) => self.app.notify("纯文本歌词没有时间戳"),
+            None => {}
+        }
+    }
+
+    fn save_offset(&mut self) {
+        let Some(track) = &self.app.current_track else {
+            return;
+        };
+        if self.app.current_lyrics.is_none() {
+            return;
+        }
+        let store = self.app.track_config.sync.save_to;
+        if track.stream && store != OffsetStore::Lrc {
+            self.app.notify("电台流只能把偏移写入 LRC 文件");
+            return;
+        }
+        let offset_ms = self.app.track_offset_ms;
+        let result = save_offset(
+            store,
+            &track.file,
+            self.app.lyrics_file.as_deref(),
+            self.app.mpd_client.as_mut(),
+            offset_ms,
+        );
+        match result {
+            Result::Ok(()) => {
+                self.app.saved_offset_ms = offset_ms;
+                self.app.notify(format!("已保存歌词偏移: {offset_ms:+} ms"));
+            }
+            Err(e) => self.app.notify(format!("保存歌词偏移失败: {e:#}")),
+        }
+    }
+
+    fn seek_to_line(&mut self, index: usize) {
+        if self.app.is_stream() {
+            self.app.notify("电台流无法跳转");
+            return;
+        }
+        let Some(lyrics) = &self.app.current_lyrics else {
+            return;
+        };
+        if !lyrics.synced {
+            self.app.notify("纯文本歌词没有时间戳");
+            return;
+        }
+        let Some(line) = lyrics.lines.get(index) else {
+            return;
+        };
+        let target = self.app.sync().playback_time(line.timestamp);
+        self.control(PlayerAction::SeekTo(target));
+    }
+
+    fn open_picker(&mut self) {
+        // 绑定以 MPD 路径为键，电台的地址对应很多首歌
+        if self.app.is_stream() {
+            self.app.notify("电台流不支持手动选择歌词");
+            return;
+        }
+        if let Some(track) = &self.app.current_track {
+            let candidates = list_candidates(&self.app.track_config, track);
+            self.app.picker = Some(LyricsPicker::new(candidates));
+        }
+    }
+
+    fn handle_picker_key_event(&mut self, event: KeyEvent) {
+        let Some(picker) = self.app.picker.as_mut() else {
+            return;
+        };
+
+        match event.code {
+            KeyCode::Esc => self.app.picker = None,
+            KeyCode::Up => picker.select_previous(),
+            KeyCode::Down => picker.select_next(),
+            KeyCode::Backspace => picker.pop_char(),
+            KeyCode::Char(c) => picker.push_char(c),
+            KeyCode::Enter => {
+                let (Some(candidate), Some(track)) =
+                    (picker.selected_candidate(), &self.app.current_track)
+                else {
+                    return;
+                };
+                let Some(binding) = candidate.binding.clone() else {
+                    picker.error = Some("该歌词来源不支持绑定".to_string());
+                    return;
+                };
+                match bind_lyrics(track, binding) {
+                    Result::Ok(()) => {
+                        let mut candidate = candidate.clone();
+                        attach_translations(&self.app.track_config, track, &mut candidate);
+                        self.app.set_lyrics(Some(candidate));
+                        self.app.picker = None;
+                    }
+                    Err(e) => picker.error = Some(format!("保存歌词绑定失败: {e}")),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn connect(&mut self) {
+        self.app.connection = ConnectionState::Connecting;
+        self.connecting = Some(spawn_connect(self.app.config.mpd.clone()));
+    }
+
+    // 按 [mpd]、[mpd.profiles.<name>]... 的顺序切换到下一个服务器，并围绕新连接重建 App
+    fn switch_server(&mut self) {
+        let servers: Vec<Option<String>> = std::iter::once(None)
+            .chain(self.servers.profiles.keys().cloned().map(Some))
+            .collect();
+        if servers.len() < 2 {
+            return;
+        }
+        let current = servers
+            .iter()
+            .position(|server| *server == self.app.server)
+            .unwrap_or(0);
+        let next = servers[(current + 1) % servers.len()].clone();
+
+        let mut config = self.app.config.clone();
+        config.mpd = match &next {
+            Some(name) => self.servers.profile(name).unwrap_or_default(),
+            None => self.servers.clone(),
+        };
+        let providers = build_providers(&config);
+        self.app = App::new(config, providers, next);
+
+        self.player_events = None;
+        self.lyrics_lookup = None;
+        self.failed_attempts = 0;
+        self.connect();
+    }
+
+    fn disconnect(&mut self, error: anyhow::Error) {
+        self.player_events = None;
+        self.failed_attempts += 1;
+        self.app.detach(ConnectionState::disconnected(
+            format!("{error:#}"),
+            self.failed_attempts,
+        ));
+    }
+
+    fn handle_connection(&mut self) {
+        if self.app.connection.should_retry() {
+            self.connect();
+        }
+        let Some(receiver) = &self.connecting else {
+            return;
+        };
+
+        let result = match receiver.try_recv() {
+            Result::Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(anyhow!("连接线程异常退出")),
+        };
+        self.connecting = None;
+
+        match result {
+            Result::Ok(session) => {
+                let changed =
+                    self.app
+                        .attach(session.client, &session.status, session.song.as_ref());
+                self.player_events = Some(session.events);
+                self.failed_attempts = 0;
+                self.last_resync = Instant::now();
+                if changed {
+                    self.request_lyrics();
+                }
+            }
+            Err(e) => self.disconnect(e),
+        }
+    }
+
+    // 只处理已到达的事件，没有事件时不访问MPD
+    pub fn handle_player_events(&mut self) -> Result<()> {
+        loop {
+            let Some(receiver) = &self.player_events else {
+                return Ok(());
+            };
+            match receiver.try_recv() {
+                Result::Ok(PlayerEvent::Changed(snapshot)) => self.handle_mpd_state(*snapshot),
+                Result::Ok(PlayerEvent::Error(e)) => return Err(anyhow!(e)),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => return Err(anyhow!("MPD事件连接已断开")),
+            }
+        }
+    }
+
+    fn handle_mpd_state(&mut self, snapshot: PlayerSnapshot) {
+        self.app.sync_clock(&snapshot.status);
+        self.app.set_outputs(snapshot.outputs);
+
+        let new_track = snapshot.song.as_ref().map(TrackInfo::from);
+        if self.app.update_current_track(new_track) {
+            self.request_lyrics();
+            self.record_missing = true;
+        }
+    }
+
+    fn request_lyrics(&mut self) {
+        self.app.set_ranked_lyrics(Vec::new());
+        self.record_missing = false;
+        // 替换接收端后，旧歌曲的查找结果会被直接丢弃
+        self.lyrics_lookup = self.app.current_track.clone().map(|track| {
+            spawn_lookup(
+                self.app.track_config.clone(),
+                self.app.track_providers(),
+                track,
+            )
+        });
+        self.app.lyrics_loading = self.lyrics_lookup.is_some();
+    }
+
+    fn handle_lyrics_lookup(&mut self) {
+        let Some(receiver) = &self.lyrics_lookup else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Result::Ok(result) => {
+                self.lyrics_lookup = None;
+                let is_current = self
+                    .app
+                    .current_track
+                    .as_ref()
+                    .is_some_and(|t| t.same_track(&result.track));
+                if is_current {
+                    if self.record_missing && result.candidates.is_empty() {
+                        self.log_missing(&result.track, result.providers);
+                    }
+                    self.app.set_ranked_lyrics(result.candidates);
+                }
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.lyrics_lookup = None;
+                self.app.lyrics_loading = false;
+            }
+        }
+    }
+
+    // 电台的地址对应很多首歌，不记录；记录失败也不影响歌词显示
+    fn log_missing(&mut self, track: &TrackInfo, providers: Vec<String>) {
+        if track.stream || !self.missing_logged.insert(track.file.clone()) {
+            return;
+        }
+        let record = MissingRecord::new(track, providers);
+        let _ = append_record(&get_missing_log_path(), &record);
+    }
+
+    // 播放中用 status 校准本地时钟，其余时间用 ping 保持命令连接，
+    // 以免超过 MPD 的 connection_timeout 后被服务器关闭
+    fn keep_alive(&mut self) {
+        let playing = self.app.play_state == MpdState::Play;
+        let interval = if playing && self.app.clock.since_sync() >= RESYNC_INTERVAL {
+            RESYNC_INTERVAL
+        } else {
+            KEEPALIVE_INTERVAL
+        };
+        if self.last_resync.elapsed() < interval {
+            return;
+        }
+        self.last_resync = Instant::now();
+        let Some(client) = self.app.mpd_client.as_mut() else {
+            return;
+        };
+
+        let result = if playing {
+            client.status().map(Some)
+        } else {
+            client.ping().map(|_| None)
+        };
+        match result {
+            Result::Ok(Some(status)) => self.app.sync_clock(&status),
+            Result::Ok(None) => {}
+            // 校准失败时继续按本地时钟滚动，连接已被服务器关闭时按断线处理并重连
+            Err(e) => {
+                let error = anyhow::Error::from(e);
+                if is_connection_lost(&error) {
+                    self.disconnect(error);
+                }
+            }
+        }
     }
 }
@@ -1,4 +1,6 @@
+pub mod ab_loop;
 pub mod app;
 pub mod controller;
+pub mod picker;
 pub mod renderer;
 pub mod widgets;
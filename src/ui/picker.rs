@@ -0,0 +1,99 @@
+use crate::lyrics::provider::LyricsCandidate;
+
+#[derive(Debug)]
+pub struct LyricsPicker {
+    pub candidates: Vec<LyricsCandidate>,
+    pub filter: String,
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl LyricsPicker {
+    pub fn new(candidates: Vec<LyricsCandidate>) -> Self {
+        Self {
+            candidates,
+            filter: String::new(),
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn filtered(&self) -> Vec<&LyricsCandidate> {
+        let filter = self.filter.to_lowercase();
+        self.candidates
+            .iter()
+            .filter(|c| filter.is_empty() || c.label.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub fn selected_candidate(&self) -> Option<&LyricsCandidate> {
+        self.filtered().get(self.selected).copied()
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.filtered().len();
+        if self.selected + 1 < len {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop_char(&mut self) {
+        self.filter.pop();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lyrics::mapping::LyricsBinding, model::lyrics::Lyrics};
+
+    fn candidate(label: &str) -> LyricsCandidate {
+        LyricsCandidate {
+            source: "embedded",
+            label: label.into(),
+            binding: Some(LyricsBinding::Embedded),
+            lyrics: Lyrics::default(),
+        }
+    }
+
+    #[test]
+    fn test_filter_and_select() {
+        let mut picker = LyricsPicker::new(vec![
+            candidate("Artist - Song.lrc"),
+            candidate("Other - Tune.lrc"),
+            candidate("artist - another song.lrc"),
+        ]);
+        assert_eq!(picker.filtered().len(), 3);
+
+        picker.select_next();
+        picker.select_next();
+        picker.select_next();
+        assert_eq!(picker.selected, 2);
+
+        for c in "song".chars() {
+            picker.push_char(c);
+        }
+        assert_eq!(picker.selected, 0);
+        assert_eq!(picker.filtered().len(), 2);
+
+        picker.select_next();
+        assert_eq!(
+            picker.selected_candidate().map(|c| c.label.as_str()),
+            Some("artist - another song.lrc")
+        );
+
+        picker.pop_char();
+        picker.push_char('x');
+        assert!(picker.selected_candidate().is_none());
+    }
+}
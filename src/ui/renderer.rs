@@ -1,26 +1,28 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, BorderType, Borders},
+    widgets::{Block, BorderType, Borders, Clear},
 };
 
-use crate::ui::{app::App, widgets::LyricsWidget};
+use crate::{
+    model::lyrics::TranslationMode,
+    player::session::ConnectionState,
+    ui::{
+        app::App,
+        widgets::{ConnectionWidget, DebugWidget, LyricsWidget, PickerWidget},
+    },
+};
 
 pub fn render(app: &App, frame: &mut Frame) {
     let full_screen = frame.area();
 
-    let file_name = app
-        .current_song_file_name
-        .as_deref()
-        .unwrap_or("Lyra 歌词播放器")
-        .to_string();
-
     let song_title = app
-        .current_song_title
-        .as_deref()
-        .unwrap_or(&file_name)
+        .current_track
+        .as_ref()
+        .map(|t| t.title.as_deref().unwrap_or(&t.file))
+        .unwrap_or("Lyra 歌词播放器")
         .to_string();
 
-    let border_block = Block::default()
+    let mut border_block = Block::default()
         .title(Span::styled(
             song_title,
             Style::default()
@@ -32,17 +34,138 @@ pub fn render(app: &App, frame: &mut Frame) {
         .border_type(BorderType::Plain)
         .border_style(Style::default().fg(Color::Cyan));
 
+    if let Some(notice) = app.current_notice() {
+        border_block = border_block.title(
+            Line::from(Span::styled(
+                format!(" {notice} "),
+                Style::default().fg(Color::Yellow),
+            ))
+            .right_aligned(),
+        );
+    }
+
+    if let Some(source) = app.lyrics_source {
+        border_block = border_block.title_bottom(
+            Line::from(Span::styled(
+                format!(" 来源: {source} "),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .right_aligned(),
+        );
+    }
+
+    if let Some(lyrics) = &app.current_lyrics
+        && !lyrics.layers.is_empty()
+    {
+        let mode = match app.translation_mode() {
+            TranslationMode::Hidden => "隐藏".to_string(),
+            TranslationMode::Layer(layer) => layer,
+            TranslationMode::All => "全部".to_string(),
+        };
+        border_block = border_block.title_bottom(
+            Line::from(Span::styled(
+                format!(" 翻译: {mode} (t) "),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .left_aligned(),
+        );
+    }
+
+    if let Some(ab_loop) = &app.ab_loop {
+        let text = match ab_loop.end_line {
+            Some(end) => {
+                let total = match ab_loop.repeat {
+                    0 => "∞".to_string(),
+                    n => n.to_string(),
+                };
+                format!(
+                    " A-B: 第 {}-{} 行 {}/{total} (x) ",
+                    ab_loop.start_line + 1,
+                    end + 1,
+                    ab_loop.completed
+                )
+            }
+            None => format!(" A: 第 {} 行 (b) ", ab_loop.start_line + 1),
+        };
+        border_block = border_block.title_bottom(
+            Line::from(Span::styled(text, Style::default().fg(Color::Yellow))).left_aligned(),
+        );
+    }
+
+    if app.track_offset_ms != 0 || app.track_offset_ms != app.saved_offset_ms {
+        // 未保存时以黄色显示
+        let (text, color) = match app.track_offset_ms == app.saved_offset_ms {
+            true => (
+                format!(" 偏移: {:+} ms ", app.track_offset_ms),
+                Color::DarkGray,
+            ),
+            false => (
+                format!(" 偏移: {:+} ms (W 保存) ", app.track_offset_ms),
+                Color::Yellow,
+            ),
+        };
+        border_block = border_block.title_bottom(
+            Line::from(Span::styled(text, Style::default().fg(color))).left_aligned(),
+        );
+    }
+
+    if !app.config.mpd.profiles.is_empty() {
+        let server = app.server.as_deref().unwrap_or("默认");
+        border_block = border_block.title_bottom(
+            Line::from(Span::styled(
+                format!(" 服务器: {server} (S) "),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .centered(),
+        );
+    }
+
     let inner_area = border_block.inner(full_screen);
 
     frame.render_widget(border_block, full_screen);
 
+    if app.connection != ConnectionState::Connected {
+        let address = app.config.mpd.get_address();
+        frame.render_widget(ConnectionWidget::new(&app.connection, &address), inner_area);
+        return;
+    }
+
     let terminal_height = inner_area.height;
 
     let lyrics_widget = LyricsWidget::new(
         &app.current_lyrics,
         app.scroll_offset,
-        &app.config.lyric_style,
+        &app.track_config.lyric_style,
         terminal_height,
-    );
+        app.lyrics_loading,
+        app.translation_mode(),
+    )
+    .with_cursor(app.line_cursor)
+    .with_loop(app.ab_loop.as_ref().map(|l| (l.start_line, l.end_line)));
     frame.render_widget(lyrics_widget, inner_area);
+
+    if app.show_debug {
+        let popup_area = centered_rect(full_screen, 80, 70);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(
+            DebugWidget::new(&app.lyrics_ranking, app.current_track.as_ref()),
+            popup_area,
+        );
+    }
+
+    if let Some(picker) = &app.picker {
+        let popup_area = centered_rect(full_screen, 80, 70);
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(PickerWidget::new(picker), popup_area);
+    }
+}
+
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Percentage(percent_y)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(layout::Flex::Center)
+        .areas(area);
+    area
 }
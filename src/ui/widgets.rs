@@ -1,14 +1,22 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Paragraph, Widget, Wrap},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
 };
 
-use crate::model::{
-    config::LyricStyleConfig,
-    lyrics::{LyricLine, Lyrics},
+use std::time::{Duration, Instant};
+
+use crate::{
+    lyrics::ranking::ScoredCandidate,
+    model::{
+        config::LyricStyleConfig,
+        lyrics::{LyricLine, Lyrics, TranslationMode},
+        track::TrackInfo,
+    },
+    player::session::ConnectionState,
+    ui::picker::LyricsPicker,
 };
 
 #[derive(Debug)]
@@ -18,6 +26,11 @@ pub struct LyricsWidget<'a> {
     scroll_offset: usize,
     style: &'a LyricStyleConfig,
     terminal_height: u16,
+    loading: bool,
+    translation: TranslationMode,
+    cursor: Option<usize>,
+    // A-B 循环的起止行，只标记了 A 点时终点为 None
+    ab_loop: Option<(usize, Option<usize>)>,
 }
 
 impl<'a> LyricsWidget<'a> {
@@ -26,22 +39,42 @@ impl<'a> LyricsWidget<'a> {
         scroll_offset: usize,
         style: &'a LyricStyleConfig,
         terminal_height: u16,
+        loading: bool,
+        translation: TranslationMode,
     ) -> Self {
         Self {
             lyrics,
             scroll_offset,
             style,
             terminal_height,
+            loading,
+            translation,
+            cursor: None,
+            ab_loop: None,
         }
     }
+
+    pub fn with_loop(mut self, ab_loop: Option<(usize, Option<usize>)>) -> Self {
+        self.ab_loop = ab_loop;
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<usize>) -> Self {
+        self.cursor = cursor;
+        self
+    }
 }
 
 impl<'a> Widget for LyricsWidget<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let lyrics_lines = match &self.lyrics {
-            Some(lyrics) => &lyrics.lines,
+        let (lyrics_lines, synced, layers) = match &self.lyrics {
+            Some(lyrics) => (&lyrics.lines, lyrics.synced, &lyrics.layers),
             None => {
-                let no_lyrics_text = "暂无歌词".to_string();
+                let no_lyrics_text = if self.loading {
+                    "正在查找歌词…"
+                } else {
+                    "暂无歌词"
+                };
                 Paragraph::new(no_lyrics_text)
                     .style(
                         Style::default()
@@ -66,19 +99,31 @@ impl<'a> Widget for LyricsWidget<'a> {
                 Modifier::empty()
             });
 
-        let current_line_style = Style::default()
-            .fg(self.parse_color(&self.style.current.color))
-            .add_modifier(if self.style.current.bold {
-                Modifier::BOLD
-            } else {
-                Modifier::empty()
-            });
+        // 纯文本歌词没有当前行
+        let current_line_style = if synced {
+            Style::default()
+                .fg(self.parse_color(&self.style.current.color))
+                .add_modifier(if self.style.current.bold {
+                    Modifier::BOLD
+                } else {
+                    Modifier::empty()
+                })
+        } else {
+            default_style
+        };
+
+        let shown_layers: Vec<&str> = layers
+            .iter()
+            .map(String::as_str)
+            .filter(|layer| self.translation.shows(layer))
+            .collect();
 
-        let visible_lines = self.calculate_visible_lines(lyrics_lines);
+        let visible_lines = self.calculate_visible_lines(lyrics_lines, 1 + shown_layers.len());
 
         let text = self.build_lyrics_text(
             lyrics_lines,
             visible_lines,
+            &shown_layers,
             default_style,
             current_line_style,
         );
@@ -92,25 +137,31 @@ impl<'a> Widget for LyricsWidget<'a> {
 
 impl<'a> LyricsWidget<'a> {
     fn parse_color(&self, color_str: &str) -> Color {
-        if let Some(hex) = color_str.strip_prefix('#') {
-            if hex.len() == 6 {
-                if let (Ok(r), Ok(g), Ok(b)) = (
-                    u8::from_str_radix(&hex[0..2], 16),
-                    u8::from_str_radix(&hex[2..4], 16),
-                    u8::from_str_radix(&hex[4..6], 16),
-                ) {
-                    return Color::Rgb(r, g, b);
-                }
-            }
+        if let Some(hex) = color_str.strip_prefix('#')
+            && hex.len() == 6
+            && let (Ok(r), Ok(g), Ok(b)) = (
+                u8::from_str_radix(&hex[0..2], 16),
+                u8::from_str_radix(&hex[2..4], 16),
+                u8::from_str_radix(&hex[4..6], 16),
+            )
+        {
+            return Color::Rgb(r, g, b);
         }
         Color::White
     }
 
-    fn calculate_visible_lines(&self, all_lines: &[LyricLine]) -> std::ops::Range<usize> {
+    // 显示翻译时每句歌词占多行，可见的歌词句数相应减少
+    fn calculate_visible_lines(
+        &self,
+        all_lines: &[LyricLine],
+        rows_per_line: usize,
+    ) -> std::ops::Range<usize> {
         let total_lines = all_lines.len();
-        let max_visible_lines = self.terminal_height as usize;
+        let max_visible_lines = (self.terminal_height as usize / rows_per_line).max(1);
 
-        let ideal_start = self.scroll_offset.saturating_sub(max_visible_lines / 2);
+        // 有选择行时以选择行为中心
+        let center = self.cursor.unwrap_or(self.scroll_offset);
+        let ideal_start = center.saturating_sub(max_visible_lines / 2);
 
         let start = if total_lines <= max_visible_lines {
             0
@@ -127,26 +178,244 @@ impl<'a> LyricsWidget<'a> {
         &self,
         all_lines: &[LyricLine],
         visible_range: std::ops::Range<usize>,
+        shown_layers: &[&str],
         default_style: Style,
         current_style: Style,
-    ) -> Text {
+    ) -> Text<'_> {
         let mut lines = Vec::new();
 
         for (line_idx, line) in all_lines.iter().enumerate() {
             if visible_range.contains(&line_idx) {
-                let style = if line_idx == self.scroll_offset {
+                let mut style = if line_idx == self.scroll_offset {
                     current_style
                 } else {
                     default_style
                 };
+                if self.cursor == Some(line_idx) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
 
-                let span = Span::styled(line.text.clone(), style);
-                let lyric_line = Line::from(span);
+                let mut spans = vec![Span::styled(line.text.clone(), style)];
+                if let Some((start, end)) = self.ab_loop {
+                    let marker = Style::default().fg(Color::Yellow);
+                    if line_idx == start {
+                        spans.insert(0, Span::styled("[A] ", marker));
+                    }
+                    if Some(line_idx) == end {
+                        spans.push(Span::styled(" [B]", marker));
+                    }
+                }
+                let lyric_line = Line::from(spans);
 
                 lines.push(lyric_line);
+
+                for layer in shown_layers {
+                    if let Some(translation) = line.translations.get(*layer) {
+                        lines.push(Line::from(Span::styled(
+                            translation.clone(),
+                            style.add_modifier(Modifier::ITALIC),
+                        )));
+                    }
+                }
             }
         }
 
         Text::from(lines)
     }
 }
+
+pub struct PickerWidget<'a> {
+    picker: &'a LyricsPicker,
+}
+
+impl<'a> PickerWidget<'a> {
+    pub fn new(picker: &'a LyricsPicker) -> Self {
+        Self { picker }
+    }
+}
+
+impl<'a> Widget for PickerWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" 选择歌词 (输入过滤 / ↑↓ 选择 / Enter 绑定 / Esc 取消) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [filter_area, body_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(body_area);
+
+        let filter_line = match &self.picker.error {
+            Some(error) => Line::styled(error.as_str(), Style::default().fg(Color::Red)),
+            None => Line::from(format!("> {}", self.picker.filter)),
+        };
+        Paragraph::new(filter_line).render(filter_area, buf);
+
+        let filtered = self.picker.filtered();
+        let height = list_area.height as usize;
+        let start = self
+            .picker
+            .selected
+            .saturating_sub(height.saturating_sub(1));
+        let items: Vec<Line> = filtered
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .map(|(i, candidate)| {
+                let style = if i == self.picker.selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default()
+                };
+                Line::styled(candidate.label.as_str(), style)
+            })
+            .collect();
+        let list = if items.is_empty() {
+            Text::from("无可选歌词")
+        } else {
+            Text::from(items)
+        };
+        Paragraph::new(list).render(list_area, buf);
+
+        let preview: Vec<Line> = filtered
+            .get(self.picker.selected)
+            .map(|candidate| {
+                candidate
+                    .lyrics
+                    .lines
+                    .iter()
+                    .take(preview_area.height as usize)
+                    .map(|line| Line::from(line.text.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Paragraph::new(preview)
+            .block(Block::default().borders(Borders::LEFT))
+            .style(Style::default().fg(Color::Gray))
+            .render(preview_area, buf);
+    }
+}
+
+pub struct DebugWidget<'a> {
+    ranking: &'a [ScoredCandidate],
+    track: Option<&'a TrackInfo>,
+}
+
+impl<'a> DebugWidget<'a> {
+    pub fn new(ranking: &'a [ScoredCandidate], track: Option<&'a TrackInfo>) -> Self {
+        Self { ranking, track }
+    }
+}
+
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(d) => format!("{:02}:{:02}", d.as_secs() / 60, d.as_secs() % 60),
+        None => "--:--".to_string(),
+    }
+}
+
+impl<'a> Widget for DebugWidget<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" 歌词候选评分 (D / Esc 关闭) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+
+        let song_length = self.track.and_then(|t| t.duration);
+        let mut lines = vec![Line::from(format!(
+            "歌曲时长 {}，共 {} 个候选",
+            format_duration(song_length),
+            self.ranking.len()
+        ))];
+
+        for (i, scored) in self.ranking.iter().enumerate() {
+            let candidate = &scored.candidate;
+            let lyrics = &candidate.lyrics;
+            let score = &scored.score;
+            let style = if i == 0 {
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let timing = match (lyrics.synced, lyrics.word_timed) {
+                (true, true) => "逐字同步",
+                (true, false) => "逐行同步",
+                _ => "纯文本",
+            };
+
+            lines.push(Line::from(""));
+            lines.push(Line::styled(
+                format!("{}. [{}] {}", i + 1, candidate.source, candidate.label),
+                style,
+            ));
+            lines.push(Line::from(format!(
+                "   总分 {:.2} = 时间轴 {:.2} + 时长 {:.2} - 无效行 {:.2}",
+                score.total(),
+                score.timing,
+                score.duration,
+                score.penalty
+            )));
+            lines.push(Line::styled(
+                format!(
+                    "   {timing} · {} 行 · 无法解析 {} 行 · 歌词结束 {}",
+                    lyrics.lines.len(),
+                    lyrics.invalid_lines,
+                    format_duration(score.lyrics_end)
+                ),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+pub struct ConnectionWidget<'a> {
+    state: &'a ConnectionState,
+    address: &'a str,
+}
+
+impl<'a> ConnectionWidget<'a> {
+    pub fn new(state: &'a ConnectionState, address: &'a str) -> Self {
+        Self { state, address }
+    }
+}
+
+impl Widget for ConnectionWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = match self.state {
+            ConnectionState::Connected => return,
+            ConnectionState::Connecting => {
+                vec![Line::from(format!("正在连接 MPD ({})…", self.address))]
+            }
+            ConnectionState::Disconnected { error, retry_at } => {
+                let remaining = retry_at.saturating_duration_since(Instant::now());
+                vec![
+                    Line::styled(
+                        format!(
+                            "MPD 连接已断开，{} 秒后重试",
+                            remaining.as_secs_f32().ceil() as u64
+                        ),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                    Line::styled(error.clone(), Style::default().fg(Color::DarkGray)),
+                ]
+            }
+        };
+
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+    }
+}